@@ -1,7 +1,9 @@
 extern crate libarp;
 
 use futures::executor::block_on;
-use libarp::{arp::ArpMessage, client::ArpClient, interfaces::Interface, interfaces::MacAddr};
+use libarp::{
+    arp::ArpMessage, client::r#async::ArpClient, interfaces::Interface, interfaces::MacAddr,
+};
 use std::net::Ipv4Addr;
 
 fn main() {
@@ -49,14 +51,14 @@ async fn resolve_advanced(mac_addr: MacAddr, ip_addr: Ipv4Addr) {
     */
 
     let arp_request =
-        ArpMessage::new_arp_request(iface.get_mac().unwrap().into(), iface.get_ip().unwrap(), ip_addr);
+        ArpMessage::new_arp_request(iface.get_mac().unwrap(), iface.get_ip().unwrap(), ip_addr);
     let result = client.send_message(None, arp_request).await.unwrap();
     println!(
         "Advanced: IP for MAC {} is {}",
         mac_addr, result.target_protocol_address
     );
 
-    let rarp_request = ArpMessage::new_rarp_request(iface.get_mac().unwrap().into(), mac_addr);
+    let rarp_request = ArpMessage::new_rarp_request(iface.get_mac().unwrap(), mac_addr);
     let result = client.send_message(None, rarp_request).await.unwrap();
     println!(
         "Advanced: MAC for IP {} is {}",
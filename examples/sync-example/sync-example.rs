@@ -1,6 +1,8 @@
 extern crate libarp;
 
-use libarp::{arp::ArpMessage, client::ArpClient, interfaces::Interface, interfaces::MacAddr};
+use libarp::{
+    arp::ArpMessage, client::blocking::ArpClient, interfaces::Interface, interfaces::MacAddr,
+};
 use std::net::Ipv4Addr;
 
 fn main() {
@@ -33,18 +35,15 @@ fn resolve_advanced(mac_addr: MacAddr, ip_addr: Ipv4Addr) {
     client's mac_to_ip and ip_to_mac methods.
     */
 
-    let arp_request = ArpMessage::new_arp_request(
-        iface.get_mac().unwrap().into(),
-        iface.get_ip().unwrap(),
-        ip_addr,
-    );
+    let arp_request =
+        ArpMessage::new_arp_request(iface.get_mac().unwrap(), iface.get_ip().unwrap(), ip_addr);
     let result = client.send_message(None, arp_request).unwrap();
     println!(
         "Advanced: IP for MAC {} is {}",
         mac_addr, result.target_protocol_address
     );
 
-    let rarp_request = ArpMessage::new_rarp_request(iface.get_mac().unwrap().into(), mac_addr);
+    let rarp_request = ArpMessage::new_rarp_request(iface.get_mac().unwrap(), mac_addr);
     let result = client.send_message(None, rarp_request).unwrap();
     println!(
         "Advanced: MAC for IP {} is {}",
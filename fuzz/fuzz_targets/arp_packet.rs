@@ -0,0 +1,14 @@
+#![no_main]
+
+use libarp::arp::{ArpMessage, ValidationPolicy};
+use libfuzzer_sys::fuzz_target;
+
+// decodes raw ARP packets (`TryFrom<ArpPacket>`), every decoded message has to survive a round trip
+fuzz_target!(|data: &[u8]| {
+    for policy in [ValidationPolicy::Lenient, ValidationPolicy::Strict] {
+        if let Ok(message) = ArpMessage::from_bytes_with_policy(data, policy) {
+            let decoded = ArpMessage::from_bytes_with_policy(&message.to_bytes(), policy);
+            assert_eq!(decoded.unwrap(), message);
+        }
+    }
+});
@@ -0,0 +1,18 @@
+#![no_main]
+
+use libarp::{
+    arp::{ArpMessage, ValidationPolicy},
+    interfaces::MacAddr,
+};
+use libfuzzer_sys::fuzz_target;
+
+// decodes Ethernet frames including VLAN tags, every decoded message has to survive a round trip
+fuzz_target!(|data: &[u8]| {
+    for policy in [ValidationPolicy::Lenient, ValidationPolicy::Strict] {
+        if let Ok(message) = ArpMessage::from_ethernet_frame_with_policy(data, policy) {
+            let frame = message.to_ethernet_frame(MacAddr::BROADCAST);
+            let decoded = ArpMessage::from_ethernet_frame_with_policy(&frame, policy);
+            assert_eq!(decoded.unwrap(), message);
+        }
+    }
+});
@@ -0,0 +1,33 @@
+#![no_main]
+
+use libarp::{
+    arp::ValidationPolicy,
+    client::blocking::ArpClient,
+    interfaces::{Interface, MacAddr},
+    transport::MemoryTransport,
+};
+use libfuzzer_sys::fuzz_target;
+use std::{net::Ipv4Addr, time::Duration};
+
+// feeds arbitrary frames to the receive path of the client, which has to skip or decode them without panicking
+fuzz_target!(|data: &[u8]| {
+    let interface = Interface::new_virtual(
+        "fuzz0",
+        MacAddr::new(0x02, 0, 0, 0, 0, 1),
+        Ipv4Addr::new(10, 0, 0, 1),
+        24,
+    )
+    .unwrap();
+
+    for policy in [ValidationPolicy::Lenient, ValidationPolicy::Strict] {
+        let transport = MemoryTransport::new();
+        transport.inject_frame(data.to_vec());
+
+        let mut client = ArpClient::new_with_transport(&interface, transport);
+        client.set_validation_policy(policy);
+
+        if let Ok(received) = client.receive(Some(Duration::ZERO)) {
+            assert_eq!(received.frame, data);
+        }
+    }
+});
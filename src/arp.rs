@@ -1,24 +1,30 @@
-use crate::interfaces::{Interface, MacAddr};
-use std::{
-    convert::TryFrom,
-    io::{Error, ErrorKind},
-    net::Ipv4Addr,
-    u16,
+use crate::{
+    error::Error,
+    interfaces::{Interface, MacAddr},
+    transport::{DefaultTransport, Transport},
+    vlan::{VlanTag, VLAN_TAG_SIZE},
 };
-
-use num_derive::FromPrimitive;
-use num_traits::FromPrimitive;
+use std::{convert::TryFrom, net::Ipv4Addr};
 
 use pnet::packet::{
     arp::{ArpHardwareTypes, ArpOperation, ArpPacket, MutableArpPacket},
     ethernet::{
         EtherType,
         EtherTypes::{self},
-        MutableEthernetPacket,
+        EthernetPacket, MutableEthernetPacket,
     },
-    MutablePacket, Packet,
+    vlan::{MutableVlanPacket, VlanPacket},
 };
 
+/// Size of an ARP packet for Ethernet hardware and IPv4 protocol addresses in bytes.
+pub const ARP_PACKET_SIZE: usize = 28;
+
+/// Size of an Ethernet frame carrying a single ARP packet (without padding and FCS) in bytes.
+pub const ARP_ETHERNET_FRAME_SIZE: usize = ETHERNET_HEADER_SIZE + ARP_PACKET_SIZE;
+
+const ETHERNET_HEADER_SIZE: usize = 14;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct ArpMessage {
     pub source_hardware_address: MacAddr,
     pub source_protocol_address: Ipv4Addr,
@@ -28,14 +34,171 @@ pub struct ArpMessage {
 
     pub ethertype: EtherType,
     pub operation: Operation,
+
+    /// VLAN tags of the frame carrying the message, outermost first. Empty for untagged frames.
+    pub vlan_tags: Vec<VlanTag>,
+
+    /// Destination address of the Ethernet frame carrying the message.
+    /// If None, requests are broadcast and responses are sent to the target hardware address.
+    pub ethernet_destination: Option<MacAddr>,
+    /// Source address of the Ethernet frame carrying the message.
+    /// If None, the address of the sending interface is used.
+    pub ethernet_source: Option<MacAddr>,
 }
 
-#[derive(Copy, Clone, FromPrimitive, PartialEq)]
+/// How strictly packets are validated when decoding them.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum ValidationPolicy {
+    /// Accepts every packet that is long enough, regardless of its hardware type, protocol type and address lengths.
+    /// Packets are always decoded as carrying Ethernet and IPv4 addresses.
+    #[default]
+    Lenient,
+    /// Only accepts packets for Ethernet hardware and IPv4 protocol addresses, with matching address lengths
+    /// and an operation code assigned in the registry.
+    Strict,
+}
+
+impl ValidationPolicy {
+    /// Checks `arp_packet` against the policy.
+    /// # Errors
+    /// Returns `Error::MalformedPacket` with the mismatching field, or `Error::UnsupportedOperation`.
+    fn validate(self, arp_packet: &ArpPacket<'_>) -> Result<(), Error> {
+        if self == ValidationPolicy::Lenient {
+            return Ok(());
+        }
+
+        let reason = if arp_packet.get_hardware_type() != ArpHardwareTypes::Ethernet {
+            format!(
+                "Unsupported hardware type {}.",
+                arp_packet.get_hardware_type().0
+            )
+        } else if arp_packet.get_protocol_type() != EtherTypes::Ipv4 {
+            format!(
+                "Unsupported protocol type {}.",
+                arp_packet.get_protocol_type()
+            )
+        } else if arp_packet.get_hw_addr_len() != 6 {
+            format!(
+                "Hardware address length {} does not match Ethernet.",
+                arp_packet.get_hw_addr_len()
+            )
+        } else if arp_packet.get_proto_addr_len() != 4 {
+            format!(
+                "Protocol address length {} does not match IPv4.",
+                arp_packet.get_proto_addr_len()
+            )
+        } else {
+            return match Operation::from(arp_packet.get_operation().0) {
+                Operation::Unknown(code) => Err(Error::UnsupportedOperation(code)),
+                _ => Ok(()),
+            };
+        };
+
+        Err(Error::MalformedPacket { reason })
+    }
+}
+
+/// ARP operation code, covering the IANA "Operation Codes (op)" registry.
+///
+/// Messages are always decoded with the fixed Ethernet/IPv4 layout. Operations using another layout, such as MARS
+/// (RFC 2022), are decoded and re-encoded as such, so their address fields are meaningless. To re-emit any
+/// received frame unchanged, send `ReceivedMessage::frame` instead of the decoded message.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum Operation {
-    ArpRequest = 0x1,
-    ArpResponse = 0x2,
-    RarpRequest = 0x3,
-    RarpResponse = 0x4,
+    ArpRequest,
+    ArpResponse,
+    RarpRequest,
+    RarpResponse,
+    DrarpRequest,
+    DrarpResponse,
+    DrarpError,
+    InArpRequest,
+    InArpResponse,
+    ArpNak,
+    MarsRequest,
+    MarsMulti,
+    MarsMserv,
+    MarsJoin,
+    MarsLeave,
+    MarsNak,
+    MarsUnserv,
+    MarsSjoin,
+    MarsSleave,
+    MarsGrouplistRequest,
+    MarsGrouplistResponse,
+    MarsRedirectMap,
+    MaposUnarp,
+    Exp1,
+    Exp2,
+    /// Operation code not assigned in the registry (or reserved), kept as is so that the message can be re-encoded.
+    /// Assigned codes are always decoded to their own variant.
+    Unknown(u16),
+}
+
+impl From<u16> for Operation {
+    fn from(code: u16) -> Self {
+        match code {
+            1 => Operation::ArpRequest,
+            2 => Operation::ArpResponse,
+            3 => Operation::RarpRequest,
+            4 => Operation::RarpResponse,
+            5 => Operation::DrarpRequest,
+            6 => Operation::DrarpResponse,
+            7 => Operation::DrarpError,
+            8 => Operation::InArpRequest,
+            9 => Operation::InArpResponse,
+            10 => Operation::ArpNak,
+            11 => Operation::MarsRequest,
+            12 => Operation::MarsMulti,
+            13 => Operation::MarsMserv,
+            14 => Operation::MarsJoin,
+            15 => Operation::MarsLeave,
+            16 => Operation::MarsNak,
+            17 => Operation::MarsUnserv,
+            18 => Operation::MarsSjoin,
+            19 => Operation::MarsSleave,
+            20 => Operation::MarsGrouplistRequest,
+            21 => Operation::MarsGrouplistResponse,
+            22 => Operation::MarsRedirectMap,
+            23 => Operation::MaposUnarp,
+            24 => Operation::Exp1,
+            25 => Operation::Exp2,
+            code => Operation::Unknown(code),
+        }
+    }
+}
+
+impl From<Operation> for u16 {
+    fn from(operation: Operation) -> Self {
+        match operation {
+            Operation::ArpRequest => 1,
+            Operation::ArpResponse => 2,
+            Operation::RarpRequest => 3,
+            Operation::RarpResponse => 4,
+            Operation::DrarpRequest => 5,
+            Operation::DrarpResponse => 6,
+            Operation::DrarpError => 7,
+            Operation::InArpRequest => 8,
+            Operation::InArpResponse => 9,
+            Operation::ArpNak => 10,
+            Operation::MarsRequest => 11,
+            Operation::MarsMulti => 12,
+            Operation::MarsMserv => 13,
+            Operation::MarsJoin => 14,
+            Operation::MarsLeave => 15,
+            Operation::MarsNak => 16,
+            Operation::MarsUnserv => 17,
+            Operation::MarsSjoin => 18,
+            Operation::MarsSleave => 19,
+            Operation::MarsGrouplistRequest => 20,
+            Operation::MarsGrouplistResponse => 21,
+            Operation::MarsRedirectMap => 22,
+            Operation::MaposUnarp => 23,
+            Operation::Exp1 => 24,
+            Operation::Exp2 => 25,
+            Operation::Unknown(code) => code,
+        }
+    }
 }
 
 impl ArpMessage {
@@ -49,12 +212,15 @@ impl ArpMessage {
         operation: Operation,
     ) -> Self {
         ArpMessage {
-            source_hardware_address: source_hardware_address,
-            source_protocol_address: source_protocol_address,
-            target_hardware_address: target_hardware_address,
-            target_protocol_address: target_protocol_address,
-            ethertype: ethertype,
-            operation: operation,
+            source_hardware_address,
+            source_protocol_address,
+            target_hardware_address,
+            target_protocol_address,
+            ethertype,
+            operation,
+            vlan_tags: Vec::new(),
+            ethernet_destination: None,
+            ethernet_source: None,
         }
     }
 
@@ -91,6 +257,37 @@ impl ArpMessage {
         )
     }
 
+    /// Constructs a new ARP probe (RFC 5227) from `source_hardware_address`, asking whether `target_protocol_address` is in use.
+    /// The sender protocol address is set to 0.0.0.0, so that the probe does not pollute the caches of other hosts.
+    pub fn new_arp_probe(
+        source_hardware_address: MacAddr,
+        target_protocol_address: Ipv4Addr,
+    ) -> Self {
+        Self::new_arp_request(
+            source_hardware_address,
+            Ipv4Addr::new(0, 0, 0, 0),
+            target_protocol_address,
+        )
+    }
+
+    /// Constructs a new gratuitous ARP request, announcing that `protocol_address` belongs to `hardware_address`.
+    /// Sender and target protocol address are both set to `protocol_address`.
+    pub fn new_gratuitous_request(hardware_address: MacAddr, protocol_address: Ipv4Addr) -> Self {
+        Self::new_arp_request(hardware_address, protocol_address, protocol_address)
+    }
+
+    /// Constructs a new gratuitous ARP response, announcing that `protocol_address` belongs to `hardware_address`.
+    /// Sender and target addresses are both set to `hardware_address` and `protocol_address`, the response is broadcast.
+    pub fn new_gratuitous_reply(hardware_address: MacAddr, protocol_address: Ipv4Addr) -> Self {
+        Self::new_arp_response(
+            hardware_address,
+            protocol_address,
+            hardware_address,
+            protocol_address,
+        )
+        .with_ethernet_destination(MacAddr::BROADCAST)
+    }
+
     /// Constructs a new RARP request message.
     pub fn new_rarp_request(
         source_hardware_address: MacAddr,
@@ -123,38 +320,252 @@ impl ArpMessage {
         )
     }
 
+    /// Constructs a new InARP request (RFC 2390), asking the host with `target_hardware_address` for its protocol addresses.
+    /// The target protocol address is set to 0.0.0.0, the request is sent to `target_hardware_address` directly.
+    pub fn new_inarp_request(
+        source_hardware_address: MacAddr,
+        source_protocol_address: Ipv4Addr,
+        target_hardware_address: MacAddr,
+    ) -> Self {
+        Self::new(
+            EtherTypes::Arp,
+            source_hardware_address,
+            source_protocol_address,
+            target_hardware_address,
+            Ipv4Addr::new(0, 0, 0, 0),
+            Operation::InArpRequest,
+        )
+        .with_ethernet_destination(target_hardware_address)
+    }
+
+    /// Constructs a new InARP response (RFC 2390), reporting `source_protocol_address` as an address of
+    /// `source_hardware_address` to the requesting host.
+    pub fn new_inarp_response(
+        source_hardware_address: MacAddr,
+        source_protocol_address: Ipv4Addr,
+        target_hardware_address: MacAddr,
+        target_protocol_address: Ipv4Addr,
+    ) -> Self {
+        Self::new(
+            EtherTypes::Arp,
+            source_hardware_address,
+            source_protocol_address,
+            target_hardware_address,
+            target_protocol_address,
+            Operation::InArpResponse,
+        )
+    }
+
+    /// Returns the message with `vlan_tag` added as innermost VLAN tag.
+    /// Adding a service tag first and a customer tag second results in a QinQ frame.
+    pub fn with_vlan_tag(mut self, vlan_tag: VlanTag) -> Self {
+        self.vlan_tags.push(vlan_tag);
+        self
+    }
+
+    /// Returns the message with the Ethernet destination address set to `mac`, e.g. for unicast requests.
+    pub fn with_ethernet_destination(mut self, mac: MacAddr) -> Self {
+        self.ethernet_destination = Some(mac);
+        self
+    }
+
+    /// Returns the message with the Ethernet source address set to `mac`.
+    pub fn with_ethernet_source(mut self, mac: MacAddr) -> Self {
+        self.ethernet_source = Some(mac);
+        self
+    }
+
+    /// Returns the destination address of the Ethernet frame carrying the message.
+    /// Unless set explicitly, this is the broadcast address for requests and the target hardware address for responses.
+    pub fn get_ethernet_destination(&self) -> MacAddr {
+        match (self.ethernet_destination, self.operation) {
+            (Some(mac), _) => mac,
+            (None, Operation::ArpResponse)
+            | (None, Operation::RarpResponse)
+            | (None, Operation::DrarpResponse)
+            | (None, Operation::DrarpError)
+            | (None, Operation::InArpResponse) => self.target_hardware_address,
+            (None, _) => MacAddr::BROADCAST,
+        }
+    }
+
+    /// Returns the VLAN ID of the innermost VLAN tag, or None if the message is untagged.
+    pub fn vlan_id(&self) -> Option<u16> {
+        self.vlan_tags.last().map(|vlan_tag| vlan_tag.id)
+    }
+
+    /// Returns whether the message is a reply to `request`: an ARP response from the requested protocol address,
+    /// a (D)RARP response or DRARP error for the requested hardware address, or an InARP response from
+    /// the requested hardware address to the requesting one. Always false if `request` is not a request.
+    pub fn is_reply_to(&self, request: &ArpMessage) -> bool {
+        match (request.operation, self.operation) {
+            (Operation::ArpRequest, Operation::ArpResponse) => {
+                self.source_protocol_address == request.target_protocol_address
+            }
+            (Operation::RarpRequest, Operation::RarpResponse)
+            | (Operation::DrarpRequest, Operation::DrarpResponse)
+            | (Operation::DrarpRequest, Operation::DrarpError) => {
+                self.target_hardware_address == request.target_hardware_address
+            }
+            (Operation::InArpRequest, Operation::InArpResponse) => {
+                self.source_hardware_address == request.target_hardware_address
+                    && self.target_hardware_address == request.source_hardware_address
+            }
+            _ => false,
+        }
+    }
+
     /// Sends the message on the given interface.
+    /// This opens a new channel on the interface for every call. To send many messages,
+    /// use `ArpClient::send`, which reuses the channel of the client.
     /// # Errors
-    /// Returns an error when sending fails.
+    /// Returns an error when opening the channel or sending fails.
     pub fn send(&self, interface: &Interface) -> Result<(), Error> {
-        let mut tx = match interface.create_tx_rx_channels() {
-            Ok((tx, _)) => tx,
-            Err(err) => return Err(err),
-        };
-
-        let mut eth_buf = vec![0; 42];
-        let mut eth_packet = MutableEthernetPacket::new(&mut eth_buf).unwrap();
+        let mut transport = DefaultTransport::new(interface).map_err(Error::from_socket)?;
 
-        eth_packet.set_destination(MacAddr::new(0xff, 0xff, 0xff, 0xff, 0xff, 0xff).into());
-        eth_packet.set_source(interface.get_mac()?.into());
-        eth_packet.set_ethertype(self.ethertype);
+        Ok(transport.send_frame(&self.to_ethernet_frame(interface.get_mac()?))?)
+    }
 
-        let mut arp_buf = vec![0; 28];
+    /// Encodes the message as ARP packet (Ethernet hardware type, IPv4 protocol type),
+    /// without any link-layer header. The hardware and protocol types and address lengths of a decoded
+    /// packet are not kept, so only Ethernet/IPv4 packets are encoded as received.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut arp_buf = vec![0; ARP_PACKET_SIZE];
         let mut arp_packet = MutableArpPacket::new(&mut arp_buf).unwrap();
 
         arp_packet.set_hardware_type(ArpHardwareTypes::Ethernet);
         arp_packet.set_protocol_type(EtherTypes::Ipv4);
         arp_packet.set_hw_addr_len(0x06);
         arp_packet.set_proto_addr_len(0x04);
-        arp_packet.set_operation(ArpOperation::new(self.operation as u16));
+        arp_packet.set_operation(ArpOperation::new(self.operation.into()));
         arp_packet.set_sender_hw_addr(self.source_hardware_address.into());
         arp_packet.set_sender_proto_addr(self.source_protocol_address);
         arp_packet.set_target_hw_addr(self.target_hardware_address.into());
         arp_packet.set_target_proto_addr(self.target_protocol_address);
 
-        eth_packet.set_payload(arp_packet.packet_mut());
+        arp_buf
+    }
+
+    /// Encodes the message as Ethernet frame to `get_ethernet_destination`, originating from the Ethernet source
+    /// address of the message, or `source_mac` if it is not set. The ethertype and VLAN tags of the frame are taken from the message.
+    pub fn to_ethernet_frame(&self, source_mac: MacAddr) -> Vec<u8> {
+        let tags_size = VLAN_TAG_SIZE * self.vlan_tags.len();
+        let mut eth_buf = vec![0; ARP_ETHERNET_FRAME_SIZE + tags_size];
+
+        // each VLAN tag is followed by the ethertype of the next tag or of the frame
+        let mut ethertypes = self
+            .vlan_tags
+            .iter()
+            .map(|vlan_tag| vlan_tag.tpid)
+            .chain(std::iter::once(self.ethertype));
+
+        let mut eth_packet = MutableEthernetPacket::new(&mut eth_buf).unwrap();
+        eth_packet.set_destination(self.get_ethernet_destination().into());
+        eth_packet.set_source(self.ethernet_source.unwrap_or(source_mac).into());
+        eth_packet.set_ethertype(ethertypes.next().unwrap());
+
+        for (i, (vlan_tag, ethertype)) in self.vlan_tags.iter().zip(ethertypes).enumerate() {
+            let offset = ETHERNET_HEADER_SIZE + VLAN_TAG_SIZE * i;
+            let mut vlan_packet = MutableVlanPacket::new(&mut eth_buf[offset..]).unwrap();
+            vlan_tag.write_to(&mut vlan_packet, ethertype);
+        }
+
+        eth_buf[ETHERNET_HEADER_SIZE + tags_size..].copy_from_slice(&self.to_bytes());
+
+        eth_buf
+    }
+
+    /// Decodes a message from a raw ARP packet, as produced by `to_bytes`.
+    /// Trailing bytes (e.g. Ethernet padding) are ignored. The packet is validated leniently.
+    /// # Errors
+    /// Returns an error if `bytes` is too short to contain an ARP packet.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        ArpMessage::from_bytes_with_policy(bytes, ValidationPolicy::Lenient)
+    }
+
+    /// Decodes a message from a raw ARP packet like `from_bytes`, validating it according to `policy`.
+    /// # Errors
+    /// Returns an error if `bytes` is too short to contain an ARP packet or if the packet is rejected by `policy`.
+    pub fn from_bytes_with_policy(bytes: &[u8], policy: ValidationPolicy) -> Result<Self, Error> {
+        if bytes.len() < ARP_PACKET_SIZE {
+            return Err(Error::MalformedPacket {
+                reason: format!(
+                    "ARP packet too short ({} bytes, expected {}).",
+                    bytes.len(),
+                    ARP_PACKET_SIZE
+                ),
+            });
+        }
+
+        match ArpPacket::new(bytes) {
+            Some(arp_packet) => {
+                policy.validate(&arp_packet)?;
+                ArpMessage::try_from(arp_packet)
+            }
+            None => Err(Error::MalformedPacket {
+                reason: "Invalid ARP packet.".to_string(),
+            }),
+        }
+    }
+
+    /// Decodes a message from a raw Ethernet frame, as produced by `to_ethernet_frame`.
+    /// The Ethernet addresses of the frame are stored in the message. VLAN-tagged frames (802.1Q, 802.1ad and QinQ)
+    /// are supported, their tags are stored in the message as well. The packet is validated leniently.
+    /// # Errors
+    /// Returns an error if the frame does not carry an ARP or RARP packet or if the packet can not be decoded.
+    pub fn from_ethernet_frame(frame: &[u8]) -> Result<Self, Error> {
+        ArpMessage::from_ethernet_frame_with_policy(frame, ValidationPolicy::Lenient)
+    }
+
+    /// Decodes a message from a raw Ethernet frame like `from_ethernet_frame`, validating it according to `policy`.
+    /// # Errors
+    /// Returns an error if the frame does not carry an ARP or RARP packet, if the packet can not be decoded
+    /// or if it is rejected by `policy`.
+    pub fn from_ethernet_frame_with_policy(
+        frame: &[u8],
+        policy: ValidationPolicy,
+    ) -> Result<Self, Error> {
+        let eth_packet = match EthernetPacket::new(frame) {
+            Some(eth_packet) => eth_packet,
+            None => {
+                return Err(Error::MalformedPacket {
+                    reason: format!("Ethernet frame too short ({} bytes).", frame.len()),
+                })
+            }
+        };
+
+        let mut ethertype = eth_packet.get_ethertype();
+        let mut offset = ETHERNET_HEADER_SIZE;
+        let mut vlan_tags = Vec::new();
+
+        while VlanTag::is_tpid(ethertype) {
+            let vlan_packet = match VlanPacket::new(&frame[offset..]) {
+                Some(vlan_packet) => vlan_packet,
+                None => {
+                    return Err(Error::MalformedPacket {
+                        reason: "Ethernet frame too short for its VLAN tags.".to_string(),
+                    })
+                }
+            };
+
+            vlan_tags.push(VlanTag::from_packet(ethertype, &vlan_packet));
+            ethertype = vlan_packet.get_ethertype();
+            offset += VLAN_TAG_SIZE;
+        }
+
+        if ethertype != EtherTypes::Arp && ethertype != EtherTypes::Rarp {
+            return Err(Error::MalformedPacket {
+                reason: format!("Ethernet frame does not carry (R)ARP, but {}.", ethertype),
+            });
+        }
+
+        let mut message = ArpMessage::from_bytes_with_policy(&frame[offset..], policy)?;
+        message.ethertype = ethertype;
+        message.vlan_tags = vlan_tags;
+        message.ethernet_destination = Some(eth_packet.get_destination().into());
+        message.ethernet_source = Some(eth_packet.get_source().into());
 
-        tx.send_to(eth_packet.packet(), None).unwrap()
+        Ok(message)
     }
 }
 
@@ -162,22 +573,20 @@ impl TryFrom<ArpPacket<'_>> for ArpMessage {
     type Error = Error;
 
     fn try_from(arp_packet: ArpPacket<'_>) -> Result<Self, Self::Error> {
-        let operation_raw = arp_packet.get_operation().0;
-        let operation = match FromPrimitive::from_u16(operation_raw) {
-            Some(op) => op,
-            None => {
-                return Err(Error::new(
-                    ErrorKind::InvalidData,
-                    format!(
-                        "Could not cast operation raw value {} to enum.",
-                        operation_raw
-                    ),
-                ))
-            }
+        let operation = Operation::from(arp_packet.get_operation().0);
+
+        // the ARP packet itself does not carry the Ethernet ethertype, so derive it from the operation
+        let ethertype = match operation {
+            Operation::RarpRequest
+            | Operation::RarpResponse
+            | Operation::DrarpRequest
+            | Operation::DrarpResponse
+            | Operation::DrarpError => EtherTypes::Rarp,
+            _ => EtherTypes::Arp,
         };
 
         Ok(ArpMessage::new(
-            arp_packet.get_protocol_type(),
+            ethertype,
             arp_packet.get_sender_hw_addr().into(),
             arp_packet.get_sender_proto_addr(),
             arp_packet.get_target_hw_addr().into(),
@@ -186,3 +595,144 @@ impl TryFrom<ArpPacket<'_>> for ArpMessage {
         ))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn mac_addr() -> impl Strategy<Value = MacAddr> {
+        any::<[u8; 6]>().prop_map(|b| MacAddr(b[0], b[1], b[2], b[3], b[4], b[5]))
+    }
+
+    fn ipv4_addr() -> impl Strategy<Value = Ipv4Addr> {
+        any::<u32>().prop_map(Ipv4Addr::from)
+    }
+
+    /// Operations from the registry as often as unknown ones.
+    fn operation() -> impl Strategy<Value = Operation> {
+        prop_oneof![1..=25u16, any::<u16>()].prop_map(Operation::from)
+    }
+
+    fn vlan_tag() -> impl Strategy<Value = VlanTag> {
+        (
+            prop_oneof![
+                Just(EtherTypes::Vlan),
+                Just(EtherTypes::PBridge),
+                Just(EtherTypes::QinQ)
+            ],
+            0..8u8,
+            any::<bool>(),
+            0..4096u16,
+        )
+            .prop_map(|(tpid, priority, drop_eligible, id)| VlanTag {
+                tpid,
+                priority,
+                drop_eligible,
+                id,
+            })
+    }
+
+    /// Messages as decoded from a raw ARP packet, with the ethertype derived from the operation.
+    fn packet_message() -> impl Strategy<Value = ArpMessage> {
+        (
+            mac_addr(),
+            ipv4_addr(),
+            mac_addr(),
+            ipv4_addr(),
+            operation(),
+        )
+            .prop_map(|(sha, spa, tha, tpa, operation)| {
+                let ethertype = match operation {
+                    Operation::RarpRequest
+                    | Operation::RarpResponse
+                    | Operation::DrarpRequest
+                    | Operation::DrarpResponse
+                    | Operation::DrarpError => EtherTypes::Rarp,
+                    _ => EtherTypes::Arp,
+                };
+
+                ArpMessage::new(ethertype, sha, spa, tha, tpa, operation)
+            })
+    }
+
+    /// Messages as decoded from an Ethernet frame, which carries the ethertype, VLAN tags and Ethernet addresses.
+    fn frame_message() -> impl Strategy<Value = ArpMessage> {
+        (
+            packet_message(),
+            prop_oneof![Just(EtherTypes::Arp), Just(EtherTypes::Rarp)],
+            prop::collection::vec(vlan_tag(), 0..4),
+            mac_addr(),
+            mac_addr(),
+        )
+            .prop_map(
+                |(message, ethertype, vlan_tags, destination, source)| ArpMessage {
+                    ethertype,
+                    vlan_tags,
+                    ethernet_destination: Some(destination),
+                    ethernet_source: Some(source),
+                    ..message
+                },
+            )
+    }
+
+    proptest! {
+        #[test]
+        fn operation_round_trip(code in any::<u16>()) {
+            prop_assert_eq!(u16::from(Operation::from(code)), code);
+        }
+
+        #[test]
+        fn bytes_round_trip(message in packet_message()) {
+            let bytes = message.to_bytes();
+
+            prop_assert_eq!(bytes.len(), ARP_PACKET_SIZE);
+            prop_assert_eq!(ArpMessage::from_bytes(&bytes).unwrap(), message);
+        }
+
+        #[test]
+        fn frame_round_trip(message in frame_message(), source_mac in mac_addr()) {
+            let frame = message.to_ethernet_frame(source_mac);
+
+            prop_assert_eq!(ArpMessage::from_ethernet_frame(&frame).unwrap(), message);
+        }
+
+        #[test]
+        fn strict_accepts_encoded(message in frame_message(), source_mac in mac_addr()) {
+            prop_assume!(!matches!(message.operation, Operation::Unknown(_)));
+            let frame = message.to_ethernet_frame(source_mac);
+
+            let decoded = ArpMessage::from_ethernet_frame_with_policy(&frame, ValidationPolicy::Strict);
+            prop_assert_eq!(decoded.unwrap(), message);
+        }
+
+        #[test]
+        fn responses_are_replies(sha in mac_addr(), spa in ipv4_addr(), tha in mac_addr(), tpa in ipv4_addr()) {
+            let arp_request = ArpMessage::new_arp_request(sha, spa, tpa);
+            let rarp_request = ArpMessage::new_rarp_request(sha, tha);
+            let inarp_request = ArpMessage::new_inarp_request(sha, spa, tha);
+
+            prop_assert!(ArpMessage::new_arp_response(tha, tpa, sha, spa).is_reply_to(&arp_request));
+            prop_assert!(ArpMessage::new_rarp_response(tha, tpa, tha, spa).is_reply_to(&rarp_request));
+            prop_assert!(ArpMessage::new_inarp_response(tha, tpa, sha, spa).is_reply_to(&inarp_request));
+            prop_assert!(!arp_request.is_reply_to(&arp_request));
+            prop_assert!(!ArpMessage::new_rarp_response(tha, tpa, tha, spa).is_reply_to(&arp_request));
+        }
+
+        #[test]
+        fn parsing_never_panics(bytes in prop::collection::vec(any::<u8>(), 0..96)) {
+            for policy in [ValidationPolicy::Lenient, ValidationPolicy::Strict] {
+                let _ = ArpMessage::from_bytes_with_policy(&bytes, policy);
+                let _ = ArpMessage::from_ethernet_frame_with_policy(&bytes, policy);
+            }
+        }
+
+        #[test]
+        fn truncated_frames_are_rejected(message in frame_message(), len in 0..ARP_ETHERNET_FRAME_SIZE) {
+            let frame = message.to_ethernet_frame(MacAddr::BROADCAST);
+            let len = len.min(frame.len() - 1);
+
+            prop_assert!(ArpMessage::from_ethernet_frame(&frame[..len]).is_err());
+        }
+    }
+}
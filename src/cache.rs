@@ -0,0 +1,414 @@
+use crate::{
+    arp::{ArpMessage, Operation},
+    interfaces::MacAddr,
+};
+use std::{
+    collections::HashMap,
+    net::Ipv4Addr,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+/// Maximum time an address is marked as being resolved, so that an abandoned lookup does not block others for long.
+const MAX_IN_FLIGHT_LIFETIME: Duration = Duration::from_secs(30);
+
+/// ARP table mapping IPv4 addresses to MAC addresses, with a limited lifetime per entry.
+///
+/// Besides resolved addresses, the cache remembers addresses that could not be resolved (negative entries)
+/// and addresses that are currently being resolved (in-flight entries), so that concurrent lookups
+/// of the same address can share one request.
+///
+/// Clones share the same table, so one cache can be used by multiple clients.
+#[derive(Clone)]
+pub struct ArpCache {
+    table: Arc<Mutex<HashMap<Ipv4Addr, CacheEntry>>>,
+    lifetime: Duration,
+    negative_lifetime: Duration,
+}
+
+/// Result of looking up an address in an `ArpCache`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CacheLookup {
+    /// The address is resolved to the contained MAC address.
+    Resolved(MacAddr),
+    /// A recent attempt to resolve the address failed.
+    Unreachable,
+    /// The address is currently being resolved.
+    InFlight,
+    /// The cache knows nothing about the address.
+    Miss,
+}
+
+/// Guard of an address being resolved, returned by `ArpCache::resolve_guard`.
+/// Removes the in-flight entry when dropped, unless a result has been stored for the address in the meantime.
+pub(crate) struct InFlightGuard {
+    cache: ArpCache,
+    ip_addr: Ipv4Addr,
+    expires_at: Instant,
+}
+
+#[derive(Copy, Clone)]
+struct CacheEntry {
+    state: EntryState,
+    expires_at: Instant,
+}
+
+#[derive(Copy, Clone)]
+enum EntryState {
+    Resolved(MacAddr),
+    Unreachable,
+    InFlight,
+}
+
+impl ArpCache {
+    /// Creates an empty cache, whose resolved entries expire after `lifetime`
+    /// and whose negative entries expire after `negative_lifetime`.
+    pub fn new(lifetime: Duration, negative_lifetime: Duration) -> Self {
+        ArpCache {
+            table: Arc::new(Mutex::new(HashMap::new())),
+            lifetime,
+            negative_lifetime,
+        }
+    }
+
+    /// Returns the state of `ip_addr` in the cache. Expired entries are removed.
+    pub fn lookup(&self, ip_addr: Ipv4Addr) -> CacheLookup {
+        ArpCache::lookup_in(&mut self.table.lock().unwrap(), ip_addr)
+    }
+
+    /// Returns the MAC address `ip_addr` is resolved to, if there is a valid entry.
+    pub fn get(&self, ip_addr: Ipv4Addr) -> Option<MacAddr> {
+        match self.lookup(ip_addr) {
+            CacheLookup::Resolved(mac_addr) => Some(mac_addr),
+            _ => None,
+        }
+    }
+
+    /// Stores that `ip_addr` is resolved to `mac_addr`, replacing any previous entry.
+    pub fn insert(&self, ip_addr: Ipv4Addr, mac_addr: MacAddr) {
+        self.table.lock().unwrap().insert(
+            ip_addr,
+            CacheEntry::new(EntryState::Resolved(mac_addr), self.lifetime),
+        );
+    }
+
+    /// Stores that `ip_addr` could not be resolved, unless it has been resolved in the meantime.
+    pub fn insert_unreachable(&self, ip_addr: Ipv4Addr) {
+        let mut table = self.table.lock().unwrap();
+
+        if let CacheLookup::Resolved(_) = ArpCache::lookup_in(&mut table, ip_addr) {
+            return;
+        }
+
+        table.insert(
+            ip_addr,
+            CacheEntry::new(EntryState::Unreachable, self.negative_lifetime),
+        );
+    }
+
+    /// Marks `ip_addr` as being resolved for at most `timeout` (and no longer than 30 seconds),
+    /// unless there is already a valid entry for it. Returns whether the caller is responsible for resolving the address.
+    pub fn begin_resolve(&self, ip_addr: Ipv4Addr, timeout: Duration) -> bool {
+        self.begin_resolve_until(ip_addr, timeout).is_some()
+    }
+
+    /// Marks `ip_addr` as being resolved like `begin_resolve`, returning a guard that removes the mark
+    /// if the lookup is abandoned, e.g. because its future is dropped.
+    pub(crate) fn resolve_guard(
+        &self,
+        ip_addr: Ipv4Addr,
+        timeout: Duration,
+    ) -> Option<InFlightGuard> {
+        self.begin_resolve_until(ip_addr, timeout)
+            .map(|expires_at| InFlightGuard {
+                cache: self.clone(),
+                ip_addr,
+                expires_at,
+            })
+    }
+
+    fn begin_resolve_until(&self, ip_addr: Ipv4Addr, timeout: Duration) -> Option<Instant> {
+        let mut table = self.table.lock().unwrap();
+
+        if ArpCache::lookup_in(&mut table, ip_addr) != CacheLookup::Miss {
+            return None;
+        }
+
+        let entry = CacheEntry::new(EntryState::InFlight, timeout.min(MAX_IN_FLIGHT_LIFETIME));
+        table.insert(ip_addr, entry);
+        Some(entry.expires_at)
+    }
+
+    /// Returns when the in-flight entry for `ip_addr` expires, if the address is currently being resolved.
+    pub(crate) fn in_flight_expiry(&self, ip_addr: Ipv4Addr) -> Option<Instant> {
+        let mut table = self.table.lock().unwrap();
+
+        match ArpCache::lookup_in(&mut table, ip_addr) {
+            CacheLookup::InFlight => table.get(&ip_addr).map(|entry| entry.expires_at),
+            _ => None,
+        }
+    }
+
+    /// Removes the entry for `ip_addr`.
+    pub fn remove(&self, ip_addr: Ipv4Addr) {
+        self.table.lock().unwrap().remove(&ip_addr);
+    }
+
+    /// Removes all entries.
+    pub fn clear(&self) {
+        self.table.lock().unwrap().clear();
+    }
+
+    /// Returns all valid resolved entries.
+    pub fn entries(&self) -> Vec<(Ipv4Addr, MacAddr)> {
+        let now = Instant::now();
+
+        self.table
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(_, entry)| entry.expires_at > now)
+            .filter_map(|(ip_addr, entry)| match entry.state {
+                EntryState::Resolved(mac_addr) => Some((*ip_addr, mac_addr)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Learns the sender addresses of an observed ARP or InARP request or response `message`.
+    /// Other operations, whose sender addresses may be missing or use another layout (e.g. MARS),
+    /// and messages without sender protocol address (e.g. probes) are ignored.
+    pub fn learn(&self, message: &ArpMessage) {
+        let has_sender_addresses = matches!(
+            message.operation,
+            Operation::ArpRequest
+                | Operation::ArpResponse
+                | Operation::InArpRequest
+                | Operation::InArpResponse
+        );
+
+        if has_sender_addresses && !message.source_protocol_address.is_unspecified() {
+            self.insert(
+                message.source_protocol_address,
+                message.source_hardware_address,
+            );
+        }
+    }
+
+    fn lookup_in(table: &mut HashMap<Ipv4Addr, CacheEntry>, ip_addr: Ipv4Addr) -> CacheLookup {
+        match table.get(&ip_addr) {
+            Some(entry) if entry.expires_at <= Instant::now() => {
+                table.remove(&ip_addr);
+                CacheLookup::Miss
+            }
+            Some(entry) => match entry.state {
+                EntryState::Resolved(mac_addr) => CacheLookup::Resolved(mac_addr),
+                EntryState::Unreachable => CacheLookup::Unreachable,
+                EntryState::InFlight => CacheLookup::InFlight,
+            },
+            None => CacheLookup::Miss,
+        }
+    }
+}
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        let mut table = self.cache.table.lock().unwrap();
+
+        // the entry may have been replaced by a result, or by another lookup after expiring
+        let is_own_entry = match table.get(&self.ip_addr) {
+            Some(entry) => {
+                matches!(entry.state, EntryState::InFlight) && entry.expires_at == self.expires_at
+            }
+            None => false,
+        };
+
+        if is_own_entry {
+            table.remove(&self.ip_addr);
+        }
+    }
+}
+
+impl CacheEntry {
+    fn new(state: EntryState, lifetime: Duration) -> Self {
+        let now = Instant::now();
+        let expires_at = now
+            .checked_add(lifetime)
+            .unwrap_or_else(|| now + Duration::from_secs(60 * 60 * 24 * 365));
+
+        CacheEntry { state, expires_at }
+    }
+}
+
+impl Default for ArpCache {
+    /// Creates an empty cache with a lifetime of 60 seconds and a negative lifetime of 5 seconds.
+    fn default() -> Self {
+        ArpCache::new(Duration::from_secs(60), Duration::from_secs(5))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Error;
+    use crate::test_util::{client, OTHER_MAC, OWN_IP, OWN_MAC, PEER_IP, PEER_MAC};
+    use std::{
+        future::Future,
+        task::{Context, Waker},
+        thread,
+    };
+
+    #[test]
+    fn entries_expire() {
+        let cache = ArpCache::new(Duration::from_millis(20), Duration::from_millis(20));
+        cache.insert(PEER_IP, PEER_MAC);
+        cache.insert_unreachable(OWN_IP);
+
+        assert_eq!(cache.lookup(PEER_IP), CacheLookup::Resolved(PEER_MAC));
+        assert_eq!(cache.lookup(OWN_IP), CacheLookup::Unreachable);
+        assert_eq!(cache.entries(), vec![(PEER_IP, PEER_MAC)]);
+
+        thread::sleep(Duration::from_millis(30));
+
+        assert_eq!(cache.lookup(PEER_IP), CacheLookup::Miss);
+        assert_eq!(cache.lookup(OWN_IP), CacheLookup::Miss);
+        assert!(cache.entries().is_empty());
+    }
+
+    #[test]
+    fn unreachable_does_not_replace_resolved() {
+        let cache = ArpCache::default();
+        cache.insert(PEER_IP, PEER_MAC);
+        cache.insert_unreachable(PEER_IP);
+
+        assert_eq!(cache.get(PEER_IP), Some(PEER_MAC));
+    }
+
+    #[test]
+    fn learns_only_arp_senders() {
+        let cache = ArpCache::default();
+        let mars = ArpMessage {
+            operation: Operation::MarsJoin,
+            ..ArpMessage::new_arp_request(OTHER_MAC, PEER_IP, OWN_IP)
+        };
+        let unknown = ArpMessage {
+            operation: Operation::Unknown(1000),
+            ..ArpMessage::new_arp_request(OTHER_MAC, PEER_IP, OWN_IP)
+        };
+
+        cache.learn(&ArpMessage::new_arp_probe(PEER_MAC, PEER_IP));
+        cache.learn(&mars);
+        cache.learn(&unknown);
+        cache.learn(&ArpMessage::new_rarp_response(
+            OTHER_MAC, PEER_IP, OWN_MAC, OWN_IP,
+        ));
+        assert_eq!(cache.lookup(PEER_IP), CacheLookup::Miss);
+
+        cache.learn(&ArpMessage::new_arp_response(
+            PEER_MAC, PEER_IP, OWN_MAC, OWN_IP,
+        ));
+        assert_eq!(cache.get(PEER_IP), Some(PEER_MAC));
+    }
+
+    #[test]
+    fn begin_resolve_only_once() {
+        let cache = ArpCache::default();
+
+        assert!(cache.begin_resolve(PEER_IP, Duration::from_secs(1)));
+        assert!(!cache.begin_resolve(PEER_IP, Duration::from_secs(1)));
+        assert_eq!(cache.lookup(PEER_IP), CacheLookup::InFlight);
+
+        cache.insert(PEER_IP, PEER_MAC);
+        assert!(!cache.begin_resolve(PEER_IP, Duration::from_secs(1)));
+    }
+
+    #[test]
+    fn lookup_resolves_after_abandoned_in_flight_request() {
+        let (mut client, transport) = client();
+        let cache = ArpCache::default();
+        client.set_cache(cache.clone());
+
+        // another user starts resolving the address, but never sends a request
+        assert!(cache.begin_resolve(PEER_IP, Duration::from_millis(100)));
+
+        let peer = thread::spawn(move || loop {
+            let sent = transport.take_sent_frames();
+
+            if let Some(frame) = sent.first() {
+                let request = ArpMessage::from_ethernet_frame(frame).unwrap();
+                let reply = ArpMessage::new_arp_response(
+                    PEER_MAC,
+                    request.target_protocol_address,
+                    request.source_hardware_address,
+                    request.source_protocol_address,
+                );
+                transport.inject_frame(reply.to_ethernet_frame(PEER_MAC));
+                return request;
+            }
+
+            thread::sleep(Duration::from_millis(5));
+        });
+
+        let start = Instant::now();
+        let result =
+            async_io::block_on(client.ip_to_mac(PEER_IP, Some(Duration::from_millis(500))));
+        let request = peer.join().unwrap();
+
+        assert!(matches!(result, Ok(mac_addr) if mac_addr == PEER_MAC));
+        assert!(start.elapsed() >= Duration::from_millis(100));
+        assert_eq!(request.target_protocol_address, PEER_IP);
+        assert_eq!(cache.get(PEER_IP), Some(PEER_MAC));
+    }
+
+    #[test]
+    fn lookup_times_out_while_request_in_flight() {
+        let (mut client, transport) = client();
+        let cache = ArpCache::default();
+        client.set_cache(cache.clone());
+
+        assert!(cache.begin_resolve(PEER_IP, Duration::from_secs(1)));
+
+        let result = async_io::block_on(client.ip_to_mac(PEER_IP, Some(Duration::from_millis(50))));
+
+        assert!(matches!(result, Err(Error::Timeout)));
+        assert!(transport.take_sent_frames().is_empty());
+    }
+
+    #[test]
+    fn in_flight_lifetime_is_limited() {
+        let cache = ArpCache::default();
+
+        assert!(cache.begin_resolve(PEER_IP, Duration::MAX));
+        let expires_at = cache.in_flight_expiry(PEER_IP).unwrap();
+        assert!(expires_at <= Instant::now() + MAX_IN_FLIGHT_LIFETIME);
+    }
+
+    #[test]
+    fn cancelled_lookup_stops_resolving() {
+        let (mut client, _transport) = client();
+        let cache = ArpCache::default();
+        client.set_cache(cache.clone());
+
+        {
+            let mut lookup = Box::pin(client.ip_to_mac(PEER_IP, None));
+            let mut cx = Context::from_waker(Waker::noop());
+            assert!(lookup.as_mut().poll(&mut cx).is_pending());
+            assert_eq!(cache.lookup(PEER_IP), CacheLookup::InFlight);
+        }
+
+        assert_eq!(cache.lookup(PEER_IP), CacheLookup::Miss);
+    }
+
+    #[test]
+    fn guard_keeps_result() {
+        let cache = ArpCache::default();
+
+        let guard = cache
+            .resolve_guard(PEER_IP, Duration::from_secs(1))
+            .unwrap();
+        cache.insert(PEER_IP, PEER_MAC);
+        drop(guard);
+
+        assert_eq!(cache.get(PEER_IP), Some(PEER_MAC));
+    }
+}
@@ -0,0 +1,831 @@
+use crate::arp::ValidationPolicy;
+use crate::cache::{ArpCache, CacheLookup};
+use crate::client::pending::{PendingRequests, PendingWait};
+use crate::client::{DuplicateAddress, ReceivedMessage, RequestId};
+use crate::error::Error;
+use crate::interfaces::MacAddr;
+use crate::monitor::ArpMonitor;
+use crate::transport::{DefaultTransport, Transport};
+use crate::vlan::VlanTag;
+use crate::{arp::ArpMessage, interfaces::Interface};
+use std::time::Duration;
+use std::{
+    future::Future,
+    net::Ipv4Addr,
+    pin::Pin,
+    task::{Context, Poll},
+    time::Instant,
+};
+
+/// Function called with received frames rejected by the validation policy, see `ArpClient::set_rejection_handler`.
+type RejectionHandler = Box<dyn FnMut(&[u8], &Error) + Send>;
+
+/// Struct that encapsulates interaction with (R)ARP messages, such as sending and receiving, using async IO.
+/// Frames are sent and received using the `Transport` `T`, which defaults to a raw socket on the interface.
+pub struct ArpClient<T: Transport = DefaultTransport> {
+    transport: T,
+    interface: Interface,
+    cache: Option<ArpCache>,
+    monitor: Option<ArpMonitor>,
+    vlan_tags: Vec<VlanTag>,
+    validation_policy: ValidationPolicy,
+    duplicate_window: Duration,
+    rejection_handler: Option<RejectionHandler>,
+    pending: PendingRequests,
+}
+
+impl ArpClient<DefaultTransport> {
+    /// Create an ARP client on a guessed, "best-suited" interface.
+    pub fn new() -> Result<Self, Error> {
+        ArpClient::new_with_iface(&Interface::new()?)
+    }
+
+    /// Create an ARP client on the interface with the name `iface_name`.
+    pub fn new_with_iface_name(iface_name: &str) -> Result<Self, Error> {
+        let iface = Interface::new_by_name(iface_name);
+
+        match iface {
+            Some(iface) => ArpClient::new_with_iface(&iface),
+            None => Err(Error::InterfaceNotFound),
+        }
+    }
+
+    /// Create an ARP client on the `interface` given.
+    /// The socket is opened once here and reused for every message sent and received by the client.
+    pub fn new_with_iface(interface: &Interface) -> Result<Self, Error> {
+        let transport = DefaultTransport::new(interface).map_err(Error::from_socket)?;
+
+        Ok(ArpClient::new_with_transport(interface, transport))
+    }
+}
+
+impl<T: Transport> ArpClient<T> {
+    /// Create an ARP client for the `interface` given, which sends and receives frames using `transport`.
+    /// The `interface` is only used for its addresses, e.g. the sender addresses of requests.
+    pub fn new_with_transport(interface: &Interface, transport: T) -> Self {
+        ArpClient {
+            transport,
+            interface: interface.clone(),
+            cache: None,
+            monitor: None,
+            vlan_tags: Vec::new(),
+            validation_policy: ValidationPolicy::default(),
+            duplicate_window: Duration::from_millis(20),
+            rejection_handler: None,
+            pending: PendingRequests::default(),
+        }
+    }
+
+    /// Returns the interface this client operates on.
+    pub fn get_interface(&self) -> &Interface {
+        &self.interface
+    }
+
+    /// Returns the transport this client sends and receives frames with.
+    pub fn get_transport(&mut self) -> &mut T {
+        &mut self.transport
+    }
+
+    /// Attaches `cache` to the client. The cache learns from every message received by the client
+    /// and is used by `ip_to_mac` to avoid sending requests.
+    pub fn set_cache(&mut self, cache: ArpCache) {
+        self.cache = Some(cache);
+    }
+
+    /// Returns the cache attached to the client, if any.
+    pub fn get_cache(&self) -> Option<&ArpCache> {
+        self.cache.as_ref()
+    }
+
+    /// Attaches `monitor` to the client. The monitor observes every message sent and received by the client.
+    pub fn set_monitor(&mut self, monitor: ArpMonitor) {
+        self.monitor = Some(monitor);
+    }
+
+    /// Returns the monitor attached to the client, if any.
+    pub fn get_monitor(&self) -> Option<&ArpMonitor> {
+        self.monitor.as_ref()
+    }
+
+    /// Lets the client operate on the VLAN given by `vlan_tags` (outermost first, e.g. for a trunk port):
+    /// messages sent without VLAN tags are tagged with `vlan_tags`, and messages received on other VLANs are ignored.
+    /// With no tags (the default), messages are sent untagged and messages on all VLANs are received.
+    pub fn set_vlan_tags(&mut self, vlan_tags: Vec<VlanTag>) {
+        self.vlan_tags = vlan_tags;
+    }
+
+    /// Returns the VLAN tags the client operates with, see `set_vlan_tags`.
+    pub fn get_vlan_tags(&self) -> &[VlanTag] {
+        &self.vlan_tags
+    }
+
+    /// Sets how strictly received packets are validated. Rejected packets are skipped like frames not carrying
+    /// an ARP message, use `set_rejection_handler` to learn about them. The default is `ValidationPolicy::Lenient`.
+    pub fn set_validation_policy(&mut self, validation_policy: ValidationPolicy) {
+        self.validation_policy = validation_policy;
+    }
+
+    /// Returns how strictly received packets are validated.
+    pub fn get_validation_policy(&self) -> ValidationPolicy {
+        self.validation_policy
+    }
+
+    /// Sets how long `ip_to_mac` keeps collecting replies after the first one, to detect other hosts using the address.
+    /// The default is 20 milliseconds.
+    pub fn set_duplicate_window(&mut self, window: Duration) {
+        self.duplicate_window = window;
+    }
+
+    /// Returns how long `ip_to_mac` keeps collecting replies after the first one.
+    pub fn get_duplicate_window(&self) -> Duration {
+        self.duplicate_window
+    }
+
+    /// Calls `handler` with every received frame that carries an ARP or RARP packet rejected by the validation policy,
+    /// along with the reason (`Error::MalformedPacket` or `Error::UnsupportedOperation`).
+    pub fn set_rejection_handler(&mut self, handler: impl FnMut(&[u8], &Error) + Send + 'static) {
+        self.rejection_handler = Some(Box::new(handler));
+    }
+
+    /// Send an ARP `message` with the given `timeout`.
+    /// Returns the next ARP message received. (must not necessarily be related to your message sent)
+    /// To wait for a reply to the message, use `send_request` and `wait_reply` instead.
+    pub async fn send_message(
+        &mut self,
+        timeout: Option<Duration>,
+        message: ArpMessage,
+    ) -> Result<ArpMessage, Error> {
+        self.send_message_with_check(timeout, message, |received| Some(received.message))
+            .await
+    }
+
+    /// Send an ARP `message` with the given `timeout`, and perform an arbitrary check `check_answer` on the answer.
+    /// Using `check_answer`, you can check if the received tmessage is related to your previously sent message if needed.
+    /// The answer is passed along with information about the frame carrying it, e.g. its receive time.
+    /// Returns the first result of `check_answer` that is not None.
+    pub async fn send_message_with_check<R>(
+        &mut self,
+        timeout: Option<Duration>,
+        message: ArpMessage,
+        check_answer: impl Fn(ReceivedMessage) -> Option<R>,
+    ) -> Result<R, Error> {
+        let deadline = deadline_after(timeout);
+
+        self.send(&message).await?;
+
+        while let Some(received) = self.receive_until(deadline).await? {
+            if let Some(result) = check_answer(received) {
+                return Ok(result);
+            }
+        }
+
+        Err(Error::Timeout)
+    }
+
+    /// Sends `request` and keeps track of it until `wait_reply` is called, so that replies to it are not lost
+    /// while waiting for replies to other requests or receiving other messages. Any number of requests can be
+    /// in flight at once, each reply is kept for every request it answers (see `ArpMessage::is_reply_to`).
+    /// Requests not waited for within 60 seconds are no longer tracked, waiting for them times out immediately.
+    pub async fn send_request(&mut self, request: &ArpMessage) -> Result<RequestId, Error> {
+        self.send(request).await?;
+
+        Ok(self.pending.insert(request.clone()))
+    }
+
+    /// Returns the first reply to the request `id`, waiting for it for at most `timeout` (or forever if None).
+    /// Replies received before, e.g. while waiting for another request, are returned immediately.
+    /// The request is no longer tracked afterwards, even if no reply has been received or the future is dropped.
+    /// # Errors
+    /// Returns `Error::Timeout` if no reply has been received in time.
+    pub async fn wait_reply(
+        &mut self,
+        id: RequestId,
+        timeout: Option<Duration>,
+    ) -> Result<ReceivedMessage, Error> {
+        let replies = self
+            .receive_replies(id, deadline_after(timeout), true)
+            .await?;
+
+        replies.into_iter().next().ok_or(Error::Timeout)
+    }
+
+    /// Resolves a given `ip_addr` to a MAC address.
+    /// To achieve this, sends an ARP request with a `timeout`.
+    ///
+    /// If a cache is attached to the client, answers from the cache if possible. If another lookup
+    /// of `ip_addr` sharing the cache is in flight, waits for its result instead of sending another request.
+    /// # Errors
+    /// Returns `Error::DuplicateAddress` if replies from multiple hosts have been received within the duplicate window
+    /// after the first reply (see `set_duplicate_window`), which may end after `timeout`.
+    /// To wait for all replies, use `ip_to_macs`.
+    pub async fn ip_to_mac(
+        &mut self,
+        ip_addr: Ipv4Addr,
+        timeout: Option<Duration>,
+    ) -> Result<MacAddr, Error> {
+        let cache = match &self.cache {
+            Some(cache) => cache.clone(),
+            None => return self.request_mac(ip_addr, timeout).await,
+        };
+
+        let deadline = deadline_after(timeout);
+
+        loop {
+            let remaining_timeout =
+                deadline.map(|deadline| deadline.saturating_duration_since(Instant::now()));
+
+            match cache.lookup(ip_addr) {
+                CacheLookup::Resolved(mac_addr) => return Ok(mac_addr),
+                CacheLookup::Unreachable => return Err(Error::Timeout),
+                // the answer to the request in flight is learned by the cache when received,
+                // if the request expires unanswered the address is resolved by this lookup
+                CacheLookup::InFlight => {
+                    let expires_at = match cache.in_flight_expiry(ip_addr) {
+                        Some(expires_at) => expires_at,
+                        None => continue,
+                    };
+                    let wait_until = match deadline {
+                        Some(deadline) => deadline.min(expires_at),
+                        None => expires_at,
+                    };
+
+                    if self.receive_until(Some(wait_until)).await?.is_none()
+                        && deadline == Some(wait_until)
+                    {
+                        return Err(Error::Timeout);
+                    }
+                }
+                CacheLookup::Miss => {
+                    let in_flight_timeout = remaining_timeout.unwrap_or(Duration::MAX);
+
+                    // the in-flight mark is removed by the guard on errors, or if this future is dropped
+                    if let Some(_guard) = cache.resolve_guard(ip_addr, in_flight_timeout) {
+                        let result = self.request_mac(ip_addr, remaining_timeout).await;
+
+                        match &result {
+                            Ok(mac_addr) => cache.insert(ip_addr, *mac_addr),
+                            Err(Error::Timeout) => cache.insert_unreachable(ip_addr),
+                            Err(_) => {}
+                        }
+
+                        return result;
+                    }
+                }
+            }
+        }
+    }
+
+    async fn request_mac(
+        &mut self,
+        ip_addr: Ipv4Addr,
+        timeout: Option<Duration>,
+    ) -> Result<MacAddr, Error> {
+        let message = ArpMessage::new_arp_request(
+            self.interface.get_mac()?,
+            self.interface.get_ip()?,
+            ip_addr,
+        );
+
+        let id = self.send_request(&message).await?;
+        let wait = self.pending.wait(id);
+
+        let result = match self.route_replies(id, deadline_after(timeout), true).await {
+            // replies from other hosts arriving shortly after the first one reveal duplicate assignments of `ip_addr`
+            Ok(()) if self.pending.has_reply(id) => {
+                let window_end = deadline_after(Some(self.duplicate_window));
+                self.route_replies(id, window_end, false).await
+            }
+            result => result,
+        };
+        let replies = wait.finish();
+        result?;
+
+        let macs = reply_addresses(&replies, |reply| reply.source_hardware_address);
+        match macs.len() {
+            0 => Err(Error::Timeout),
+            1 => Ok(macs[0]),
+            _ => Err(DuplicateAddress { ip: ip_addr, macs }.into()),
+        }
+    }
+
+    /// Resolves a given `ip_addr` to all MAC addresses answering within `window`, in order of arrival.
+    /// More than one address means that `ip_addr` is in use by multiple hosts (or e.g. a misconfigured
+    /// VRRP/HSRP group). Always sends a request, even if a cache is attached to the client.
+    /// # Errors
+    /// Returns `Error::Timeout` if no host answered.
+    pub async fn ip_to_macs(
+        &mut self,
+        ip_addr: Ipv4Addr,
+        window: Duration,
+    ) -> Result<Vec<MacAddr>, Error> {
+        let message = ArpMessage::new_arp_request(
+            self.interface.get_mac()?,
+            self.interface.get_ip()?,
+            ip_addr,
+        );
+        let id = self.send_request(&message).await?;
+        let replies = self
+            .receive_replies(id, deadline_after(Some(window)), false)
+            .await?;
+
+        let macs = reply_addresses(&replies, |reply| reply.source_hardware_address);
+        if macs.is_empty() {
+            return Err(Error::Timeout);
+        }
+
+        Ok(macs)
+    }
+
+    /// Resolves a given `mac_addr` to an IPv4 address.
+    /// To achieve this, sends an RARP request with a `timeout`.
+    pub async fn mac_to_ip(
+        &mut self,
+        mac_addr: MacAddr,
+        timeout: Option<Duration>,
+    ) -> Result<Ipv4Addr, Error> {
+        let message = ArpMessage::new_rarp_request(self.interface.get_mac()?, mac_addr);
+
+        let id = self.send_request(&message).await?;
+        let reply = self.wait_reply(id, timeout).await?;
+
+        // the RARP response is sent by the server, the requested MAC address is the target (RFC 903)
+        Ok(reply.message.target_protocol_address)
+    }
+
+    /// Resolves a given `mac_addr` to all IPv4 addresses reported within `window`, using Inverse ARP (RFC 2390).
+    /// Unlike `mac_to_ip`, this does not depend on a RARP server, but the host with `mac_addr` has to answer
+    /// InARP requests itself.
+    /// # Errors
+    /// Returns `Error::Timeout` if the host did not answer.
+    pub async fn inverse_resolve(
+        &mut self,
+        mac_addr: MacAddr,
+        window: Duration,
+    ) -> Result<Vec<Ipv4Addr>, Error> {
+        let message = ArpMessage::new_inarp_request(
+            self.interface.get_mac()?,
+            self.interface.get_ip()?,
+            mac_addr,
+        );
+
+        let id = self.send_request(&message).await?;
+        let replies = self
+            .receive_replies(id, deadline_after(Some(window)), false)
+            .await?;
+
+        let ip_addrs = reply_addresses(&replies, |reply| reply.source_protocol_address);
+        if ip_addrs.is_empty() {
+            return Err(Error::Timeout);
+        }
+
+        Ok(ip_addrs)
+    }
+
+    /// Announces that `ip_addr` belongs to the interface of this client by sending `count` gratuitous ARP requests,
+    /// waiting `interval` between them. Neighbors update their caches when receiving the announcements,
+    /// e.g. after moving a virtual IP address to this host.
+    pub async fn announce(
+        &mut self,
+        ip_addr: Ipv4Addr,
+        count: usize,
+        interval: Duration,
+    ) -> Result<(), Error> {
+        let announcement = ArpMessage::new_gratuitous_request(self.interface.get_mac()?, ip_addr);
+
+        for i in 0..count {
+            if i > 0 {
+                self.idle(interval).await?;
+            }
+
+            self.send(&announcement).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Sends `arp_message` on the interface belonging to this client.
+    pub async fn send(&mut self, arp_message: &ArpMessage) -> Result<(), Error> {
+        let frame = if arp_message.vlan_tags.is_empty() && !self.vlan_tags.is_empty() {
+            let tagged_message = ArpMessage {
+                vlan_tags: self.vlan_tags.clone(),
+                ..arp_message.clone()
+            };
+            tagged_message.to_ethernet_frame(self.interface.get_mac()?)
+        } else {
+            arp_message.to_ethernet_frame(self.interface.get_mac()?)
+        };
+        self.transport.send_frame(&frame)?;
+
+        if let Some(monitor) = &self.monitor {
+            monitor.observe_sent(arp_message);
+        }
+
+        Ok(())
+    }
+
+    /// Returns the next ARP message received, waiting for it if necessary.
+    /// Frames not containing an ARP message are skipped. Returns None if receiving fails.
+    pub async fn receive_next(&mut self) -> Option<ArpMessage> {
+        match self.receive_until(None).await {
+            Ok(received) => received.map(|received| received.message),
+            Err(_) => None,
+        }
+    }
+
+    /// Returns the next ARP message received within `timeout` (or ever if None), along with information
+    /// about the frame carrying it. Frames not containing an ARP message are skipped.
+    /// # Errors
+    /// Returns `Error::Timeout` if no message has been received in time, or when receiving fails.
+    pub async fn receive(&mut self, timeout: Option<Duration>) -> Result<ReceivedMessage, Error> {
+        match self.receive_until(deadline_after(timeout)).await? {
+            Some(received) => Ok(received),
+            None => Err(Error::Timeout),
+        }
+    }
+
+    /// Keeps tracking the request `id` until the returned guard is finished, which returns the replies
+    /// received for it in order of arrival, or dropped.
+    pub(crate) fn wait_request(&self, id: RequestId) -> PendingWait {
+        self.pending.wait(id)
+    }
+
+    /// Receives messages until `deadline` (or ever if None), or only until a reply to the request `id` has been
+    /// received if `first_only`, and returns the replies to the request. The request is no longer tracked afterwards.
+    async fn receive_replies(
+        &mut self,
+        id: RequestId,
+        deadline: Option<Instant>,
+        first_only: bool,
+    ) -> Result<Vec<ReceivedMessage>, Error> {
+        let wait = self.pending.wait(id);
+        let result = self.route_replies(id, deadline, first_only).await;
+        let replies = wait.finish();
+        result?;
+
+        Ok(replies)
+    }
+
+    /// Receives messages until `deadline` (or ever if None), or only until a reply to the request `id` has been
+    /// received if `first_only`. Replies are kept in the table of pending requests.
+    async fn route_replies(
+        &mut self,
+        id: RequestId,
+        deadline: Option<Instant>,
+        first_only: bool,
+    ) -> Result<(), Error> {
+        while !(first_only && self.pending.has_reply(id)) {
+            if self.receive_until(deadline).await?.is_none() {
+                break;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the next ARP message received before `deadline` (or ever if None), or None if there is none.
+    /// Waits for frames and the deadline without blocking, using the `async-io` reactor and timers.
+    /// # Errors
+    /// Returns an error when receiving fails.
+    pub(crate) async fn receive_until(
+        &mut self,
+        deadline: Option<Instant>,
+    ) -> Result<Option<ReceivedMessage>, Error> {
+        let mut timer = deadline.map(async_io::Timer::at);
+
+        std::future::poll_fn(|cx| {
+            if let Poll::Ready(result) = self.poll_receive(cx) {
+                return Poll::Ready(result.map(Some));
+            }
+
+            match timer.as_mut().map(|timer| Pin::new(timer).poll(cx)) {
+                Some(Poll::Ready(_)) => Poll::Ready(Ok(None)),
+                _ => Poll::Pending,
+            }
+        })
+        .await
+    }
+
+    /// Polls for the next ARP message received. If none is available, arranges for the task of `cx`
+    /// to be woken up when a frame arrives.
+    pub(crate) fn poll_receive(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<ReceivedMessage, Error>> {
+        loop {
+            match self.transport.receive_frame(Some(Duration::ZERO)) {
+                Ok(Some(frame)) => {
+                    if let Some(received) = process_frame(
+                        frame,
+                        self.interface.get_mac().ok(),
+                        self.validation_policy,
+                        &mut self.rejection_handler,
+                        &self.vlan_tags,
+                        &self.cache,
+                        &self.monitor,
+                    ) {
+                        self.pending.route(&received);
+                        return Poll::Ready(Ok(received));
+                    }
+                    continue;
+                }
+                Ok(None) => {}
+                Err(err) => return Poll::Ready(Err(err.into())),
+            }
+
+            match self.transport.poll_readable(cx) {
+                Poll::Ready(Ok(())) => continue,
+                Poll::Ready(Err(err)) => return Poll::Ready(Err(err.into())),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+
+    /// Keeps receiving messages for `duration`, so that they are processed (e.g. learned by the cache) while waiting.
+    pub(crate) async fn idle(&mut self, duration: Duration) -> Result<(), Error> {
+        let deadline = deadline_after(Some(duration));
+        while self.receive_until(deadline).await?.is_some() {}
+
+        Ok(())
+    }
+}
+
+/// Returns the address selected by `address` of each of `replies`, without duplicates and in order of arrival.
+pub(crate) fn reply_addresses<A: PartialEq>(
+    replies: &[ReceivedMessage],
+    address: impl Fn(&ArpMessage) -> A,
+) -> Vec<A> {
+    let mut addresses = Vec::new();
+    for reply in replies {
+        let reply_address = address(&reply.message);
+        if !addresses.contains(&reply_address) {
+            addresses.push(reply_address);
+        }
+    }
+
+    addresses
+}
+
+/// Returns the point in time `timeout` from now, or None if there is no `timeout` or it is too large to be represented.
+pub(crate) fn deadline_after(timeout: Option<Duration>) -> Option<Instant> {
+    timeout.and_then(|timeout| Instant::now().checked_add(timeout))
+}
+
+/// Decodes the ARP message in `frame`, if any and on the VLAN of `vlan_tags`, and lets `cache` and `monitor` observe it.
+/// Messages sent from `own_mac`, e.g. by the operating system, are observed by the monitor as sent by the interface.
+/// Packets rejected by `validation_policy` are passed to `rejection_handler`.
+fn process_frame(
+    frame: &[u8],
+    own_mac: Option<MacAddr>,
+    validation_policy: ValidationPolicy,
+    rejection_handler: &mut Option<RejectionHandler>,
+    vlan_tags: &[VlanTag],
+    cache: &Option<ArpCache>,
+    monitor: &Option<ArpMonitor>,
+) -> Option<ReceivedMessage> {
+    let message = match ArpMessage::from_ethernet_frame_with_policy(frame, validation_policy) {
+        Ok(message) => message,
+        Err(err) => {
+            // unlike other frames failing to decode, frames only rejected by the policy carry (R)ARP
+            if let Some(rejection_handler) = rejection_handler {
+                if validation_policy != ValidationPolicy::Lenient
+                    && ArpMessage::from_ethernet_frame(frame).is_ok()
+                {
+                    rejection_handler(frame, &err);
+                }
+            }
+            return None;
+        }
+    };
+
+    let vlan_ids = vlan_tags.iter().map(|vlan_tag| vlan_tag.id);
+    if !vlan_tags.is_empty()
+        && !message
+            .vlan_tags
+            .iter()
+            .map(|vlan_tag| vlan_tag.id)
+            .eq(vlan_ids)
+    {
+        return None;
+    }
+
+    if let Some(cache) = cache {
+        cache.learn(&message);
+    }
+
+    if let Some(monitor) = monitor {
+        match own_mac {
+            Some(own_mac) if message.source_hardware_address == own_mac => {
+                monitor.observe_own(&message, message.ethernet_source)
+            }
+            _ => monitor.observe(&message, message.ethernet_source),
+        }
+    }
+
+    Some(ReceivedMessage::new(message, frame))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::arp::Operation;
+    use crate::test_util::{
+        client, inject, sent_messages, OTHER_MAC, OWN_IP, OWN_MAC, PEER_IP, PEER_MAC,
+    };
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn ip_to_mac_sends_request_and_returns_reply() {
+        let (mut client, transport) = client();
+        inject(
+            &transport,
+            &ArpMessage::new_arp_response(PEER_MAC, PEER_IP, OWN_MAC, OWN_IP),
+        );
+
+        let mac_addr =
+            async_io::block_on(client.ip_to_mac(PEER_IP, Some(Duration::from_millis(100))));
+        assert_eq!(mac_addr.unwrap(), PEER_MAC);
+
+        let sent = sent_messages(&transport);
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].operation, Operation::ArpRequest);
+        assert_eq!(sent[0].source_protocol_address, OWN_IP);
+        assert_eq!(sent[0].target_protocol_address, PEER_IP);
+    }
+
+    #[test]
+    fn ip_to_mac_times_out_without_reply() {
+        let (mut client, transport) = client();
+        // a reply for another address must not be taken as answer
+        inject(
+            &transport,
+            &ArpMessage::new_arp_response(PEER_MAC, Ipv4Addr::new(10, 0, 0, 3), OWN_MAC, OWN_IP),
+        );
+
+        let result = async_io::block_on(client.ip_to_mac(PEER_IP, Some(Duration::from_millis(20))));
+        assert!(matches!(result, Err(Error::Timeout)));
+    }
+
+    #[test]
+    fn mac_to_ip_returns_rarp_response() {
+        let (mut client, transport) = client();
+        inject(
+            &transport,
+            &ArpMessage::new_rarp_response(PEER_MAC, PEER_IP, OWN_MAC, OWN_IP),
+        );
+
+        let ip_addr =
+            async_io::block_on(client.mac_to_ip(OWN_MAC, Some(Duration::from_millis(100))));
+        assert_eq!(ip_addr.unwrap(), OWN_IP);
+
+        let sent = sent_messages(&transport);
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].operation, Operation::RarpRequest);
+        assert_eq!(sent[0].target_hardware_address, OWN_MAC);
+    }
+
+    #[test]
+    fn send_message_with_check_skips_unrelated_messages() {
+        let (mut client, transport) = client();
+        inject(
+            &transport,
+            &ArpMessage::new_arp_request(PEER_MAC, PEER_IP, Ipv4Addr::new(10, 0, 0, 3)),
+        );
+        inject(
+            &transport,
+            &ArpMessage::new_arp_response(PEER_MAC, PEER_IP, OWN_MAC, OWN_IP),
+        );
+
+        let request = ArpMessage::new_arp_request(OWN_MAC, OWN_IP, PEER_IP);
+        let result = async_io::block_on(client.send_message_with_check(
+            Some(Duration::from_millis(100)),
+            request,
+            |received| match received.message.operation {
+                Operation::ArpResponse => Some(received.message.source_hardware_address),
+                _ => None,
+            },
+        ));
+        assert_eq!(result.unwrap(), PEER_MAC);
+    }
+
+    #[test]
+    fn rejected_frames_are_reported() {
+        let (mut client, transport) = client();
+        client.set_validation_policy(ValidationPolicy::Strict);
+
+        let rejected = Arc::new(Mutex::new(Vec::new()));
+        let handler_rejected = rejected.clone();
+        client.set_rejection_handler(move |_, err| {
+            handler_rejected.lock().unwrap().push(err.kind());
+        });
+
+        let reply = ArpMessage::new_arp_response(PEER_MAC, PEER_IP, OWN_MAC, OWN_IP);
+        let mut unsupported = reply.to_ethernet_frame(PEER_MAC);
+        // operation code, following the Ethernet header and the ARP fixed header fields
+        unsupported[20..22].copy_from_slice(&0x00ffu16.to_be_bytes());
+        transport.inject_frame(unsupported);
+        inject(&transport, &reply);
+
+        let received = async_io::block_on(client.receive(Some(Duration::from_millis(100))));
+        assert_eq!(received.unwrap().message.operation, Operation::ArpResponse);
+        assert_eq!(
+            *rejected.lock().unwrap(),
+            vec![std::io::ErrorKind::Unsupported]
+        );
+    }
+
+    #[test]
+    fn interleaved_replies_reach_their_requests() {
+        let (mut client, transport) = client();
+        let other_ip = Ipv4Addr::new(10, 0, 0, 3);
+
+        let first = async_io::block_on(
+            client.send_request(&ArpMessage::new_arp_request(OWN_MAC, OWN_IP, PEER_IP)),
+        )
+        .unwrap();
+        let second = async_io::block_on(
+            client.send_request(&ArpMessage::new_arp_request(OWN_MAC, OWN_IP, other_ip)),
+        )
+        .unwrap();
+
+        inject(
+            &transport,
+            &ArpMessage::new_arp_response(OTHER_MAC, other_ip, OWN_MAC, OWN_IP),
+        );
+        inject(
+            &transport,
+            &ArpMessage::new_arp_response(PEER_MAC, PEER_IP, OWN_MAC, OWN_IP),
+        );
+
+        let timeout = Some(Duration::from_millis(100));
+        let first_reply = async_io::block_on(client.wait_reply(first, timeout)).unwrap();
+        let second_reply = async_io::block_on(client.wait_reply(second, timeout)).unwrap();
+
+        assert_eq!(first_reply.message.source_hardware_address, PEER_MAC);
+        assert_eq!(second_reply.message.source_hardware_address, OTHER_MAC);
+        assert_eq!(client.pending.len(), 0);
+    }
+
+    #[test]
+    fn cancelled_wait_stops_tracking() {
+        let (mut client, _transport) = client();
+        let id = async_io::block_on(
+            client.send_request(&ArpMessage::new_arp_request(OWN_MAC, OWN_IP, PEER_IP)),
+        )
+        .unwrap();
+
+        {
+            let mut wait = Box::pin(client.wait_reply(id, None));
+            let mut cx = Context::from_waker(std::task::Waker::noop());
+            assert!(wait.as_mut().poll(&mut cx).is_pending());
+        }
+
+        assert_eq!(client.pending.len(), 0);
+    }
+
+    #[test]
+    fn ip_to_macs_returns_every_host() {
+        let (mut client, transport) = client();
+        for mac_addr in [PEER_MAC, OTHER_MAC, PEER_MAC] {
+            inject(
+                &transport,
+                &ArpMessage::new_arp_response(mac_addr, PEER_IP, OWN_MAC, OWN_IP),
+            );
+        }
+
+        let macs = async_io::block_on(client.ip_to_macs(PEER_IP, Duration::from_millis(20)));
+        assert_eq!(macs.unwrap(), vec![PEER_MAC, OTHER_MAC]);
+
+        let macs = async_io::block_on(client.ip_to_macs(PEER_IP, Duration::from_millis(20)));
+        assert!(matches!(macs, Err(Error::Timeout)));
+    }
+
+    #[test]
+    fn ip_to_mac_detects_late_duplicate() {
+        let (mut client, transport) = client();
+        inject(
+            &transport,
+            &ArpMessage::new_arp_response(PEER_MAC, PEER_IP, OWN_MAC, OWN_IP),
+        );
+
+        let late_transport = transport.clone();
+        let other_host = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(5));
+            inject(
+                &late_transport,
+                &ArpMessage::new_arp_response(OTHER_MAC, PEER_IP, OWN_MAC, OWN_IP),
+            );
+        });
+
+        let result =
+            async_io::block_on(client.ip_to_mac(PEER_IP, Some(Duration::from_millis(100))));
+        other_host.join().unwrap();
+
+        match result {
+            Err(Error::DuplicateAddress(duplicate)) => {
+                assert_eq!(duplicate.ip, PEER_IP);
+                assert_eq!(duplicate.macs, vec![PEER_MAC, OTHER_MAC]);
+            }
+            result => panic!("unexpected result {:?}", result),
+        }
+    }
+}
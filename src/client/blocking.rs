@@ -0,0 +1,230 @@
+use super::{r#async, ReceivedMessage, RequestId};
+use crate::arp::{ArpMessage, ValidationPolicy};
+use crate::cache::ArpCache;
+use crate::error::Error;
+use crate::interfaces::{Interface, MacAddr};
+use crate::monitor::ArpMonitor;
+use crate::transport::{DefaultTransport, Transport};
+use crate::vlan::VlanTag;
+use std::{net::Ipv4Addr, time::Duration};
+
+/// Struct that encapsulates interaction with (R)ARP messages, such as sending and receiving, using blocking IO.
+/// Runs the operations of the async `ArpClient` to completion on the current thread, see `client::r#async::ArpClient`.
+pub struct ArpClient<T: Transport = DefaultTransport> {
+    client: r#async::ArpClient<T>,
+}
+
+impl ArpClient<DefaultTransport> {
+    /// Create an ARP client on a guessed, "best-suited" interface.
+    pub fn new() -> Result<Self, Error> {
+        r#async::ArpClient::new().map(ArpClient::from)
+    }
+
+    /// Create an ARP client on the interface with the name `iface_name`.
+    pub fn new_with_iface_name(iface_name: &str) -> Result<Self, Error> {
+        r#async::ArpClient::new_with_iface_name(iface_name).map(ArpClient::from)
+    }
+
+    /// Create an ARP client on the `interface` given.
+    /// The socket is opened once here and reused for every message sent and received by the client.
+    pub fn new_with_iface(interface: &Interface) -> Result<Self, Error> {
+        r#async::ArpClient::new_with_iface(interface).map(ArpClient::from)
+    }
+}
+
+impl<T: Transport> ArpClient<T> {
+    /// Create an ARP client for the `interface` given, which sends and receives frames using `transport`.
+    /// The `interface` is only used for its addresses, e.g. the sender addresses of requests.
+    pub fn new_with_transport(interface: &Interface, transport: T) -> Self {
+        r#async::ArpClient::new_with_transport(interface, transport).into()
+    }
+
+    /// Returns the async client this client runs operations on.
+    pub(crate) fn as_async(&mut self) -> &mut r#async::ArpClient<T> {
+        &mut self.client
+    }
+
+    /// Returns the interface this client operates on.
+    pub fn get_interface(&self) -> &Interface {
+        self.client.get_interface()
+    }
+
+    /// Returns the transport this client sends and receives frames with.
+    pub fn get_transport(&mut self) -> &mut T {
+        self.client.get_transport()
+    }
+
+    /// Attaches `cache` to the client. The cache learns from every message received by the client
+    /// and is used by `ip_to_mac` to avoid sending requests.
+    pub fn set_cache(&mut self, cache: ArpCache) {
+        self.client.set_cache(cache)
+    }
+
+    /// Returns the cache attached to the client, if any.
+    pub fn get_cache(&self) -> Option<&ArpCache> {
+        self.client.get_cache()
+    }
+
+    /// Attaches `monitor` to the client. The monitor observes every message sent and received by the client.
+    pub fn set_monitor(&mut self, monitor: ArpMonitor) {
+        self.client.set_monitor(monitor)
+    }
+
+    /// Returns the monitor attached to the client, if any.
+    pub fn get_monitor(&self) -> Option<&ArpMonitor> {
+        self.client.get_monitor()
+    }
+
+    /// Lets the client operate on the VLAN given by `vlan_tags`, see `r#async::ArpClient::set_vlan_tags`.
+    pub fn set_vlan_tags(&mut self, vlan_tags: Vec<VlanTag>) {
+        self.client.set_vlan_tags(vlan_tags)
+    }
+
+    /// Returns the VLAN tags the client operates with.
+    pub fn get_vlan_tags(&self) -> &[VlanTag] {
+        self.client.get_vlan_tags()
+    }
+
+    /// Sets how strictly received packets are validated, see `r#async::ArpClient::set_validation_policy`.
+    pub fn set_validation_policy(&mut self, validation_policy: ValidationPolicy) {
+        self.client.set_validation_policy(validation_policy)
+    }
+
+    /// Returns how strictly received packets are validated.
+    pub fn get_validation_policy(&self) -> ValidationPolicy {
+        self.client.get_validation_policy()
+    }
+
+    /// Sets how long `ip_to_mac` keeps collecting replies after the first one, see `r#async::ArpClient::set_duplicate_window`.
+    pub fn set_duplicate_window(&mut self, window: Duration) {
+        self.client.set_duplicate_window(window)
+    }
+
+    /// Returns how long `ip_to_mac` keeps collecting replies after the first one.
+    pub fn get_duplicate_window(&self) -> Duration {
+        self.client.get_duplicate_window()
+    }
+
+    /// Calls `handler` with received frames rejected by the validation policy, see `r#async::ArpClient::set_rejection_handler`.
+    pub fn set_rejection_handler(&mut self, handler: impl FnMut(&[u8], &Error) + Send + 'static) {
+        self.client.set_rejection_handler(handler)
+    }
+
+    /// Send an ARP `message` with the given `timeout`.
+    /// Returns the next ARP message received. (must not necessarily be related to your message sent)
+    pub fn send_message(
+        &mut self,
+        timeout: Option<Duration>,
+        message: ArpMessage,
+    ) -> Result<ArpMessage, Error> {
+        async_io::block_on(self.client.send_message(timeout, message))
+    }
+
+    /// Send an ARP `message` with the given `timeout`, and perform an arbitrary check `check_answer` on the answer.
+    /// Returns the first result of `check_answer` that is not None.
+    pub fn send_message_with_check<R>(
+        &mut self,
+        timeout: Option<Duration>,
+        message: ArpMessage,
+        check_answer: impl Fn(ReceivedMessage) -> Option<R>,
+    ) -> Result<R, Error> {
+        async_io::block_on(
+            self.client
+                .send_message_with_check(timeout, message, check_answer),
+        )
+    }
+
+    /// Sends `request` and keeps track of it until `wait_reply` is called, see `r#async::ArpClient::send_request`.
+    pub fn send_request(&mut self, request: &ArpMessage) -> Result<RequestId, Error> {
+        async_io::block_on(self.client.send_request(request))
+    }
+
+    /// Returns the first reply to the request `id`, waiting for it for at most `timeout` (or forever if None).
+    /// # Errors
+    /// Returns `Error::Timeout` if no reply has been received in time.
+    pub fn wait_reply(
+        &mut self,
+        id: RequestId,
+        timeout: Option<Duration>,
+    ) -> Result<ReceivedMessage, Error> {
+        async_io::block_on(self.client.wait_reply(id, timeout))
+    }
+
+    /// Resolves a given `ip_addr` to a MAC address.
+    /// To achieve this, sends an ARP request with a `timeout`.
+    /// # Errors
+    /// Returns `Error::DuplicateAddress` if multiple hosts answered, see `r#async::ArpClient::ip_to_mac`.
+    pub fn ip_to_mac(
+        &mut self,
+        ip_addr: Ipv4Addr,
+        timeout: Option<Duration>,
+    ) -> Result<MacAddr, Error> {
+        async_io::block_on(self.client.ip_to_mac(ip_addr, timeout))
+    }
+
+    /// Resolves a given `ip_addr` to all MAC addresses answering within `window`, see `r#async::ArpClient::ip_to_macs`.
+    pub fn ip_to_macs(
+        &mut self,
+        ip_addr: Ipv4Addr,
+        window: Duration,
+    ) -> Result<Vec<MacAddr>, Error> {
+        async_io::block_on(self.client.ip_to_macs(ip_addr, window))
+    }
+
+    /// Resolves a given `mac_addr` to an IPv4 address.
+    /// To achieve this, sends an RARP request with a `timeout`.
+    pub fn mac_to_ip(
+        &mut self,
+        mac_addr: MacAddr,
+        timeout: Option<Duration>,
+    ) -> Result<Ipv4Addr, Error> {
+        async_io::block_on(self.client.mac_to_ip(mac_addr, timeout))
+    }
+
+    /// Resolves a given `mac_addr` to all IPv4 addresses reported within `window`, using Inverse ARP (RFC 2390).
+    /// # Errors
+    /// Returns `Error::Timeout` if the host did not answer.
+    pub fn inverse_resolve(
+        &mut self,
+        mac_addr: MacAddr,
+        window: Duration,
+    ) -> Result<Vec<Ipv4Addr>, Error> {
+        async_io::block_on(self.client.inverse_resolve(mac_addr, window))
+    }
+
+    /// Announces that `ip_addr` belongs to the interface of this client by sending `count` gratuitous ARP requests,
+    /// waiting `interval` between them.
+    pub fn announce(
+        &mut self,
+        ip_addr: Ipv4Addr,
+        count: usize,
+        interval: Duration,
+    ) -> Result<(), Error> {
+        async_io::block_on(self.client.announce(ip_addr, count, interval))
+    }
+
+    /// Sends `arp_message` on the interface belonging to this client.
+    pub fn send(&mut self, arp_message: &ArpMessage) -> Result<(), Error> {
+        async_io::block_on(self.client.send(arp_message))
+    }
+
+    /// Returns the next ARP message received, waiting for it if necessary.
+    /// Frames not containing an ARP message are skipped. Returns None if receiving fails.
+    pub fn receive_next(&mut self) -> Option<ArpMessage> {
+        async_io::block_on(self.client.receive_next())
+    }
+
+    /// Returns the next ARP message received within `timeout` (or ever if None), along with information
+    /// about the frame carrying it. Frames not containing an ARP message are skipped.
+    /// # Errors
+    /// Returns `Error::Timeout` if no message has been received in time, or when receiving fails.
+    pub fn receive(&mut self, timeout: Option<Duration>) -> Result<ReceivedMessage, Error> {
+        async_io::block_on(self.client.receive(timeout))
+    }
+}
+
+impl<T: Transport> From<r#async::ArpClient<T>> for ArpClient<T> {
+    fn from(client: r#async::ArpClient<T>) -> Self {
+        ArpClient { client }
+    }
+}
@@ -0,0 +1,25 @@
+use crate::interfaces::MacAddr;
+use std::{fmt, net::Ipv4Addr};
+
+/// Error returned by `ArpClient::ip_to_mac` if more than one host answered for an IPv4 address.
+/// It is returned as `Error::DuplicateAddress`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DuplicateAddress {
+    /// The IPv4 address that has been resolved.
+    pub ip: Ipv4Addr,
+    /// All MAC addresses that answered, in order of arrival.
+    pub macs: Vec<MacAddr>,
+}
+
+impl fmt::Display for DuplicateAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} is in use by multiple hosts:", self.ip)?;
+        for mac in &self.macs {
+            write!(f, " {}", mac)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl std::error::Error for DuplicateAddress {}
@@ -0,0 +1,12 @@
+/// ARP client using async IO.
+pub mod r#async;
+/// ARP client using blocking IO, built on top of the async client.
+pub mod blocking;
+mod duplicate;
+pub(crate) mod pending;
+mod received;
+
+pub use self::duplicate::DuplicateAddress;
+pub use self::pending::RequestId;
+pub use self::r#async::ArpClient;
+pub use self::received::ReceivedMessage;
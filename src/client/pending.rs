@@ -0,0 +1,217 @@
+use crate::{arp::ArpMessage, client::ReceivedMessage};
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+/// Time after which a request that is not waited for is no longer tracked, see `ArpClient::send_request`.
+pub(crate) const REQUEST_LIFETIME: Duration = Duration::from_secs(60);
+
+/// Identifies a request sent with `ArpClient::send_request`, to wait for its reply with `ArpClient::wait_reply`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct RequestId(u64);
+
+/// Table of the requests of a client awaiting replies, along with the replies received for them so far.
+///
+/// Requests being waited for are tracked until their `PendingWait` is finished or dropped, e.g. when the waiting
+/// future is cancelled. Other requests expire after `REQUEST_LIFETIME`. Clones share the same table.
+#[derive(Clone, Debug)]
+pub(crate) struct PendingRequests {
+    table: Arc<Mutex<PendingTable>>,
+}
+
+/// Guard of a request being waited for. The request is no longer tracked once the guard is finished or dropped.
+pub(crate) struct PendingWait {
+    requests: PendingRequests,
+    id: RequestId,
+}
+
+#[derive(Debug)]
+struct PendingTable {
+    next_id: u64,
+    requests: Vec<PendingRequest>,
+    lifetime: Duration,
+}
+
+#[derive(Debug)]
+struct PendingRequest {
+    id: RequestId,
+    request: ArpMessage,
+    replies: Vec<ReceivedMessage>,
+    expires_at: Instant,
+    waited: bool,
+}
+
+impl PendingRequests {
+    /// Creates an empty table, whose requests expire after `lifetime` if not waited for.
+    fn new(lifetime: Duration) -> Self {
+        PendingRequests {
+            table: Arc::new(Mutex::new(PendingTable {
+                next_id: 0,
+                requests: Vec::new(),
+                lifetime,
+            })),
+        }
+    }
+
+    /// Adds `request` to the table.
+    pub(crate) fn insert(&self, request: ArpMessage) -> RequestId {
+        let mut table = self.table.lock().unwrap();
+        table.prune();
+
+        let id = RequestId(table.next_id);
+        let expires_at = Instant::now() + table.lifetime;
+        table.next_id += 1;
+
+        table.requests.push(PendingRequest {
+            id,
+            request,
+            replies: Vec::new(),
+            expires_at,
+            waited: false,
+        });
+
+        id
+    }
+
+    /// Stores `received` for every pending request it is a reply to.
+    pub(crate) fn route(&self, received: &ReceivedMessage) {
+        let mut table = self.table.lock().unwrap();
+        table.prune();
+
+        for pending in &mut table.requests {
+            if received.message.is_reply_to(&pending.request) {
+                pending.replies.push(received.clone());
+            }
+        }
+    }
+
+    /// Returns whether a reply to the request `id` has been received, or the request is not pending at all.
+    pub(crate) fn has_reply(&self, id: RequestId) -> bool {
+        let table = self.table.lock().unwrap();
+
+        match table.requests.iter().find(|pending| pending.id == id) {
+            Some(pending) => !pending.replies.is_empty(),
+            None => true,
+        }
+    }
+
+    /// Marks the request `id` as being waited for, so that it does not expire until the returned guard
+    /// is finished or dropped.
+    pub(crate) fn wait(&self, id: RequestId) -> PendingWait {
+        let mut table = self.table.lock().unwrap();
+
+        if let Some(pending) = table.requests.iter_mut().find(|pending| pending.id == id) {
+            pending.waited = true;
+        }
+
+        PendingWait {
+            requests: self.clone(),
+            id,
+        }
+    }
+
+    /// Removes the request `id` from the table and returns its replies, in order of arrival.
+    fn finish(&self, id: RequestId) -> Vec<ReceivedMessage> {
+        let mut table = self.table.lock().unwrap();
+
+        match table.requests.iter().position(|pending| pending.id == id) {
+            Some(index) => table.requests.swap_remove(index).replies,
+            None => Vec::new(),
+        }
+    }
+
+    #[cfg(test)]
+    pub(crate) fn len(&self) -> usize {
+        self.table.lock().unwrap().requests.len()
+    }
+}
+
+impl PendingWait {
+    /// Stops tracking the request and returns the replies received for it, in order of arrival.
+    pub(crate) fn finish(self) -> Vec<ReceivedMessage> {
+        self.requests.finish(self.id)
+    }
+}
+
+impl Drop for PendingWait {
+    fn drop(&mut self) {
+        self.requests.finish(self.id);
+    }
+}
+
+impl Default for PendingRequests {
+    fn default() -> Self {
+        PendingRequests::new(REQUEST_LIFETIME)
+    }
+}
+
+impl PendingTable {
+    /// Removes the requests that are not waited for and have expired.
+    fn prune(&mut self) {
+        let now = Instant::now();
+
+        self.requests
+            .retain(|pending| pending.waited || pending.expires_at > now);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{OWN_IP, OWN_MAC, PEER_MAC};
+    use std::{net::Ipv4Addr, thread};
+
+    fn request(host: u8) -> ArpMessage {
+        ArpMessage::new_arp_request(OWN_MAC, OWN_IP, Ipv4Addr::new(10, 0, 0, host))
+    }
+
+    fn reply(host: u8) -> ReceivedMessage {
+        let message =
+            ArpMessage::new_arp_response(PEER_MAC, Ipv4Addr::new(10, 0, 0, host), OWN_MAC, OWN_IP);
+        let frame = message.to_ethernet_frame(PEER_MAC);
+
+        ReceivedMessage::new(message, &frame)
+    }
+
+    #[test]
+    fn routes_replies_to_their_requests() {
+        let pending = PendingRequests::default();
+        let first = pending.insert(request(2));
+        let second = pending.insert(request(3));
+
+        pending.route(&reply(3));
+        assert!(!pending.has_reply(first));
+        assert!(pending.has_reply(second));
+
+        pending.route(&reply(2));
+        assert_eq!(pending.wait(first).finish().len(), 1);
+        assert_eq!(pending.wait(second).finish().len(), 1);
+        assert_eq!(pending.len(), 0);
+    }
+
+    #[test]
+    fn expires_requests_not_waited_for() {
+        let pending = PendingRequests::new(Duration::from_millis(20));
+        let waited = pending.insert(request(2));
+        let wait = pending.wait(waited);
+        pending.insert(request(3));
+
+        thread::sleep(Duration::from_millis(30));
+        pending.route(&reply(2));
+
+        assert_eq!(pending.len(), 1);
+        assert_eq!(wait.finish().len(), 1);
+    }
+
+    #[test]
+    fn dropping_wait_stops_tracking() {
+        let pending = PendingRequests::default();
+        let id = pending.insert(request(2));
+
+        drop(pending.wait(id));
+
+        assert_eq!(pending.len(), 0);
+        assert!(pending.has_reply(id));
+    }
+}
@@ -0,0 +1,64 @@
+use crate::{arp::ArpMessage, interfaces::MacAddr, vlan::VlanTag};
+use pnet::packet::ethernet::EtherType;
+use std::time::{Instant, SystemTime};
+
+/// ARP message received by an `ArpClient`, along with information about the frame carrying it.
+///
+/// The times are taken when the client dequeues the frame from its transport, not by the kernel on arrival.
+/// They include any delay until the frame is processed, e.g. while the client is busy sending requests.
+#[derive(Clone, Debug)]
+pub struct ReceivedMessage {
+    /// The decoded message.
+    pub message: ArpMessage,
+    /// Wall clock time the frame was dequeued at, e.g. for captures.
+    pub timestamp: SystemTime,
+    /// Monotonic time the frame was dequeued at, e.g. to measure round-trip times.
+    pub received_at: Instant,
+    /// The raw Ethernet frame, including VLAN tags and padding. Unlike the decoded message,
+    /// it can be re-emitted unchanged, whatever the hardware and protocol types of its packet.
+    pub frame: Vec<u8>,
+}
+
+impl ReceivedMessage {
+    /// Captures `frame`, decoded to `message`, as dequeued now.
+    pub(crate) fn new(message: ArpMessage, frame: &[u8]) -> Self {
+        ReceivedMessage {
+            message,
+            timestamp: SystemTime::now(),
+            received_at: Instant::now(),
+            frame: frame.to_vec(),
+        }
+    }
+
+    /// Returns the source address of the Ethernet frame.
+    pub fn ethernet_source(&self) -> MacAddr {
+        self.message
+            .ethernet_source
+            .unwrap_or(self.message.source_hardware_address)
+    }
+
+    /// Returns the destination address of the Ethernet frame.
+    pub fn ethernet_destination(&self) -> MacAddr {
+        self.message.get_ethernet_destination()
+    }
+
+    /// Returns the ethertype of the frame (ARP or RARP), following any VLAN tags.
+    pub fn ethertype(&self) -> EtherType {
+        self.message.ethertype
+    }
+
+    /// Returns the VLAN tags of the frame, outermost first.
+    pub fn vlan_tags(&self) -> &[VlanTag] {
+        &self.message.vlan_tags
+    }
+
+    /// Returns the VLAN ID of the innermost VLAN tag, or None if the frame is untagged.
+    pub fn vlan_id(&self) -> Option<u16> {
+        self.message.vlan_id()
+    }
+
+    /// Returns the length of the frame in bytes.
+    pub fn frame_len(&self) -> usize {
+        self.frame.len()
+    }
+}
@@ -0,0 +1,112 @@
+use crate::client::DuplicateAddress;
+use std::{fmt, io};
+
+/// Error returned by the fallible functions of this crate.
+/// Converts into an `io::Error` of a matching kind, for callers that still work with `io::Error`.
+#[derive(Debug)]
+pub enum Error {
+    /// No answer has been received in time.
+    Timeout,
+    /// No network interface with the requested name exists, or none could be selected.
+    InterfaceNotFound,
+    /// The interface does not have an IPv4 address assigned.
+    NoIpv4Address,
+    /// The interface does not have a MAC address assigned.
+    NoMacAddress,
+    /// Opening a raw socket has been denied, usually because the `CAP_NET_RAW` capability is missing.
+    PermissionDenied,
+    /// A frame or packet could not be decoded.
+    MalformedPacket { reason: String },
+    /// The operation code of a packet is not supported.
+    UnsupportedOperation(u16),
+    /// An argument, such as a subnet or MAC address, is invalid.
+    InvalidInput { reason: String },
+    /// Multiple hosts answered for an IPv4 address, see `DuplicateAddress`.
+    DuplicateAddress(DuplicateAddress),
+    /// Any other IO error, e.g. of the transport.
+    Io(io::Error),
+}
+
+impl Error {
+    /// Converts an error opening a raw socket, reporting a denied permission as `PermissionDenied`.
+    pub(crate) fn from_socket(err: io::Error) -> Self {
+        match err.kind() {
+            io::ErrorKind::PermissionDenied => Error::PermissionDenied,
+            _ => Error::Io(err),
+        }
+    }
+
+    /// Returns the `io::ErrorKind` the error is converted to.
+    pub fn kind(&self) -> io::ErrorKind {
+        match self {
+            Error::Timeout => io::ErrorKind::TimedOut,
+            Error::InterfaceNotFound => io::ErrorKind::NotFound,
+            Error::NoIpv4Address | Error::NoMacAddress => io::ErrorKind::AddrNotAvailable,
+            Error::PermissionDenied => io::ErrorKind::PermissionDenied,
+            Error::MalformedPacket { .. } => io::ErrorKind::InvalidData,
+            Error::UnsupportedOperation(_) => io::ErrorKind::Unsupported,
+            Error::InvalidInput { .. } => io::ErrorKind::InvalidInput,
+            Error::DuplicateAddress(_) => io::ErrorKind::AddrInUse,
+            Error::Io(err) => err.kind(),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Timeout => write!(f, "Timeout"),
+            Error::InterfaceNotFound => write!(f, "No such interface."),
+            Error::NoIpv4Address => write!(
+                f,
+                "Currently selected interface does not have any IP address assigned."
+            ),
+            Error::NoMacAddress => write!(
+                f,
+                "Currently selected interface does not have any MAC address assigned."
+            ),
+            Error::PermissionDenied => write!(
+                f,
+                "Permission denied, raw sockets require the CAP_NET_RAW capability."
+            ),
+            Error::MalformedPacket { reason } => write!(f, "Malformed packet: {}", reason),
+            Error::UnsupportedOperation(operation) => {
+                write!(f, "Unsupported operation {}.", operation)
+            }
+            Error::InvalidInput { reason } => write!(f, "{}", reason),
+            Error::DuplicateAddress(duplicate) => write!(f, "{}", duplicate),
+            Error::Io(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::DuplicateAddress(duplicate) => Some(duplicate),
+            Error::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Error::Io(err)
+    }
+}
+
+impl From<DuplicateAddress> for Error {
+    fn from(duplicate: DuplicateAddress) -> Self {
+        Error::DuplicateAddress(duplicate)
+    }
+}
+
+impl From<Error> for io::Error {
+    fn from(err: Error) -> Self {
+        match err {
+            Error::Io(err) => err,
+            err => io::Error::new(err.kind(), err),
+        }
+    }
+}
@@ -1,11 +1,9 @@
-use pnet::datalink::{
-    channel, interfaces, Channel, DataLinkReceiver, DataLinkSender, NetworkInterface,
-};
-use std::{
-    io::{Error, ErrorKind},
-    net::{IpAddr, Ipv4Addr},
-    time::Duration,
+use crate::error::Error;
+use pnet::{
+    datalink::{channel, interfaces, Channel, DataLinkReceiver, DataLinkSender, NetworkInterface},
+    ipnetwork::{IpNetwork, Ipv4Network},
 };
+use std::{io, net::Ipv4Addr, time::Duration};
 
 /// Represents a network interface.
 /// Wraps pnet's `NetworkInterface` struct for better convenience.
@@ -21,55 +19,67 @@ impl Interface {
             Some(iface) => Ok(Interface {
                 network_interface: iface,
             }),
-            None => Err(Error::new(
-                ErrorKind::NotConnected,
-                "Could not get any network interface.",
-            )),
+            None => Err(Error::InterfaceNotFound),
         }
     }
 
     /// Selects the interface with the name `interface_name`.
     pub fn new_by_name(interface_name: &str) -> Option<Self> {
-        let iface = Interface::get_interface_by_name(&interface_name);
+        Interface::get_interface_by_name(interface_name).map(|iface| Interface {
+            network_interface: iface,
+        })
+    }
 
-        match iface {
-            Some(iface) => Some(Interface {
-                network_interface: iface,
-            }),
-            None => None,
-        }
+    /// Creates an interface that does not exist on the system, having the name `interface_name`,
+    /// the MAC address `mac` and the IPv4 address `ip` with the network prefix length `prefix`.
+    /// Useful in combination with a transport that does not use the real network, such as `MemoryTransport`.
+    pub fn new_virtual(
+        interface_name: &str,
+        mac: MacAddr,
+        ip: Ipv4Addr,
+        prefix: u8,
+    ) -> Result<Self, Error> {
+        let network = match Ipv4Network::new(ip, prefix) {
+            Ok(network) => network,
+            Err(err) => {
+                return Err(Error::InvalidInput {
+                    reason: err.to_string(),
+                })
+            }
+        };
+
+        Ok(Interface {
+            network_interface: NetworkInterface {
+                name: interface_name.to_string(),
+                description: String::new(),
+                index: 0,
+                mac: Some(mac.into()),
+                ips: vec![IpNetwork::V4(network)],
+                flags: 0,
+            },
+        })
     }
 
     /// Returns the IPv4 address of the interface.
     pub fn get_ip(&self) -> Result<Ipv4Addr, Error> {
-        let ip = self
-            .network_interface
-            .ips
-            .iter()
-            .find(|ip| ip.is_ipv4())
-            .map(|ip| match ip.ip() {
-                IpAddr::V4(ip) => Some(ip),
-                _ => None,
-            })
-            .unwrap_or(None);
-
-        match ip {
-            Some(ip) => Ok(ip),
-            None => Err(Error::new(
-                ErrorKind::AddrNotAvailable,
-                "Currently selected interface does not have any IP address assigned.",
-            )),
-        }
+        Ok(self.get_ipv4_network()?.ip())
+    }
+
+    /// Returns the netmask belonging to the IPv4 address of the interface.
+    pub fn get_netmask(&self) -> Result<Ipv4Addr, Error> {
+        Ok(self.get_ipv4_network()?.mask())
+    }
+
+    /// Returns the network prefix length belonging to the IPv4 address of the interface.
+    pub fn get_prefix(&self) -> Result<u8, Error> {
+        Ok(self.get_ipv4_network()?.prefix())
     }
 
     /// Returns the MAC address assigned to the interface.
     pub fn get_mac(&self) -> Result<MacAddr, Error> {
         match self.network_interface.mac {
             Some(mac) => Ok(mac.into()),
-            None => Err(Error::new(
-                ErrorKind::AddrNotAvailable,
-                "Currently selected interface does not have any MAC address assigned.",
-            )),
+            None => Err(Error::NoMacAddress),
         }
     }
 
@@ -79,6 +89,7 @@ impl Interface {
     }
 
     /// Creates and returns a new Ethernet (tx, rx) channel pair on the interface.
+    #[allow(clippy::type_complexity)]
     pub fn create_tx_rx_channels(
         &self,
     ) -> Result<(Box<dyn DataLinkSender>, Box<dyn DataLinkReceiver>), Error> {
@@ -87,10 +98,26 @@ impl Interface {
             ..Default::default()
         };
 
-        match channel(&self.get_raw_interface(), channel_config) {
+        match channel(self.get_raw_interface(), channel_config) {
             Ok(Channel::Ethernet(tx, rx)) => Ok((tx, rx)),
-            Ok(_) => return Err(Error::new(ErrorKind::Other, "Unknown channel type")),
-            Err(err) => return Err(err),
+            Ok(_) => Err(Error::Io(io::Error::other("Unknown channel type"))),
+            Err(err) => Err(Error::from_socket(err)),
+        }
+    }
+
+    fn get_ipv4_network(&self) -> Result<Ipv4Network, Error> {
+        let network = self
+            .network_interface
+            .ips
+            .iter()
+            .find_map(|network| match network {
+                IpNetwork::V4(network) => Some(*network),
+                _ => None,
+            });
+
+        match network {
+            Some(network) => Ok(network),
+            None => Err(Error::NoIpv4Address),
         }
     }
 
@@ -104,10 +131,7 @@ impl Interface {
             .filter(|iface| !iface.is_loopback() && iface.is_up() && !iface.ips.is_empty())
             .collect::<Vec<NetworkInterface>>();
 
-        match considered_ifaces.first() {
-            Some(iface) => Some(iface.clone()),
-            None => None,
-        }
+        considered_ifaces.first().cloned()
     }
 
     fn get_interface_by_name(name: &str) -> Option<NetworkInterface> {
@@ -116,18 +140,18 @@ impl Interface {
             .filter(|iface| iface.name == *name)
             .collect::<Vec<NetworkInterface>>();
 
-        match considered_ifaces.first() {
-            Some(iface) => Some(iface.clone()),
-            None => None,
-        }
+        considered_ifaces.first().cloned()
     }
 }
 
 /// Redefinition of the pnet `MacAddr`, so that as a user pnet does not need to be imported
-#[derive(PartialEq, Eq, Clone, Copy)]
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug, Hash)]
 pub struct MacAddr(pub u8, pub u8, pub u8, pub u8, pub u8, pub u8);
 
 impl MacAddr {
+    /// The Ethernet broadcast address ff:ff:ff:ff:ff:ff.
+    pub const BROADCAST: MacAddr = MacAddr(0xff, 0xff, 0xff, 0xff, 0xff, 0xff);
+
     pub fn new(a: u8, b: u8, c: u8, d: u8, e: u8, f: u8) -> Self {
         MacAddr(a, b, c, d, e, f)
     }
@@ -143,6 +167,20 @@ impl std::fmt::Display for MacAddr {
     }
 }
 
+impl std::str::FromStr for MacAddr {
+    type Err = Error;
+
+    /// Parses a MAC address in colon-separated hexadecimal notation, e.g. `dc:a6:32:27:5b:d8`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.parse::<pnet::util::MacAddr>() {
+            Ok(pnet_mac_addr) => Ok(pnet_mac_addr.into()),
+            Err(err) => Err(Error::InvalidInput {
+                reason: format!("Invalid MAC address {}: {}", s, err),
+            }),
+        }
+    }
+}
+
 impl From<pnet::util::MacAddr> for MacAddr {
     fn from(pnet_mac_addr: pnet::util::MacAddr) -> Self {
         MacAddr(
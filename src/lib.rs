@@ -1,3 +1,18 @@
 pub mod arp;
+pub mod cache;
 pub mod client;
+pub mod error;
 pub mod interfaces;
+pub mod monitor;
+pub mod ping;
+pub mod probe;
+pub mod rarp_server;
+pub mod resolve;
+pub mod responder;
+pub mod scan;
+pub mod stream;
+pub mod transport;
+pub mod vlan;
+
+#[cfg(test)]
+mod test_util;
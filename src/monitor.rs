@@ -0,0 +1,359 @@
+use crate::{
+    arp::{ArpMessage, Operation},
+    interfaces::MacAddr,
+};
+use std::{
+    collections::{HashMap, HashSet},
+    net::Ipv4Addr,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+/// Suspicious activity detected by an `ArpMonitor`, hinting at ARP spoofing or poisoning.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MonitorEvent {
+    /// `ip` was claimed by `old_mac` before and is now claimed by `new_mac`.
+    MacChanged {
+        ip: Ipv4Addr,
+        old_mac: MacAddr,
+        new_mac: MacAddr,
+    },
+    /// `mac` claims as many IPv4 addresses as configured in `MonitorOptions::max_addresses_per_mac`.
+    ManyAddresses { mac: MacAddr, ips: Vec<Ipv4Addr> },
+    /// The sender hardware address of a message for `ip` differs from the Ethernet source address of its frame.
+    SourceMismatch {
+        ip: Ipv4Addr,
+        sender_mac: MacAddr,
+        ethernet_source: MacAddr,
+    },
+    /// `mac` replied for `ip`, although no request for `ip` has been sent recently.
+    UnsolicitedReply { ip: Ipv4Addr, mac: MacAddr },
+}
+
+/// Thresholds of an `ArpMonitor`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MonitorOptions {
+    /// Number of IPv4 addresses one MAC address may claim before `MonitorEvent::ManyAddresses` is raised.
+    pub max_addresses_per_mac: usize,
+    /// Time after sending a request for an address during which replies for it are expected.
+    pub reply_window: Duration,
+    /// Number of events kept until they are taken. If exceeded, the oldest events are discarded.
+    pub max_events: usize,
+}
+
+impl Default for MonitorOptions {
+    fn default() -> Self {
+        MonitorOptions {
+            max_addresses_per_mac: 16,
+            reply_window: Duration::from_secs(10),
+            max_events: 1024,
+        }
+    }
+}
+
+/// Passive monitor that observes ARP messages and raises `MonitorEvent`s for suspicious activity.
+///
+/// Attach it to a client with `ArpClient::set_monitor` to observe every message the client sends and receives,
+/// or feed it manually. Clones share the same state, so events can be taken from a clone.
+///
+/// At most `MonitorOptions::max_events` events are kept until they are taken. The observed bindings are kept
+/// until `clear` is called, so they grow with the number of distinct addresses seen on the network.
+#[derive(Clone, Default)]
+pub struct ArpMonitor {
+    state: Arc<Mutex<MonitorState>>,
+    options: MonitorOptions,
+}
+
+#[derive(Default)]
+struct MonitorState {
+    bindings: HashMap<Ipv4Addr, MacAddr>,
+    addresses_per_mac: HashMap<MacAddr, HashSet<Ipv4Addr>>,
+    request_times: HashMap<Ipv4Addr, Instant>,
+    events: Vec<MonitorEvent>,
+}
+
+impl ArpMonitor {
+    /// Creates a monitor with the thresholds in `options`.
+    pub fn new(options: MonitorOptions) -> Self {
+        ArpMonitor {
+            state: Default::default(),
+            options,
+        }
+    }
+
+    /// Observes a received `message`. `ethernet_source` is the source address of the Ethernet frame
+    /// carrying the message, if known.
+    pub fn observe(&self, message: &ArpMessage, ethernet_source: Option<MacAddr>) {
+        self.observe_message(message, ethernet_source, false);
+    }
+
+    /// Observes a `message` sent by the own interface, but not by the client, e.g. by the operating system.
+    /// Requests are treated like requests passed to `observe_sent`, and replies are never considered unsolicited.
+    pub fn observe_own(&self, message: &ArpMessage, ethernet_source: Option<MacAddr>) {
+        self.observe_sent(message);
+        self.observe_message(message, ethernet_source, true);
+    }
+
+    fn observe_message(&self, message: &ArpMessage, ethernet_source: Option<MacAddr>, own: bool) {
+        let mut state = self.state.lock().unwrap();
+        let ip = message.source_protocol_address;
+        let mac = message.source_hardware_address;
+
+        if let Some(ethernet_source) = ethernet_source {
+            if ethernet_source != mac {
+                self.push_event(
+                    &mut state,
+                    MonitorEvent::SourceMismatch {
+                        ip,
+                        sender_mac: mac,
+                        ethernet_source,
+                    },
+                );
+            }
+        }
+
+        if message.operation == Operation::ArpResponse && !own {
+            let is_solicited = match state.request_times.get(&ip) {
+                Some(request_time) => request_time.elapsed() <= self.options.reply_window,
+                None => false,
+            };
+
+            if !is_solicited {
+                self.push_event(&mut state, MonitorEvent::UnsolicitedReply { ip, mac });
+            }
+        }
+
+        let is_arp = message.operation == Operation::ArpRequest
+            || message.operation == Operation::ArpResponse;
+        if !is_arp || ip.is_unspecified() {
+            return;
+        }
+
+        if let Some(old_mac) = state.bindings.insert(ip, mac) {
+            if old_mac != mac {
+                self.push_event(
+                    &mut state,
+                    MonitorEvent::MacChanged {
+                        ip,
+                        old_mac,
+                        new_mac: mac,
+                    },
+                );
+
+                if let Some(ips) = state.addresses_per_mac.get_mut(&old_mac) {
+                    ips.remove(&ip);
+                }
+            }
+        }
+
+        let ips = state.addresses_per_mac.entry(mac).or_default();
+        if ips.insert(ip) && ips.len() == self.options.max_addresses_per_mac {
+            let mut ips = ips.iter().copied().collect::<Vec<Ipv4Addr>>();
+            ips.sort();
+
+            self.push_event(&mut state, MonitorEvent::ManyAddresses { mac, ips });
+        }
+    }
+
+    fn push_event(&self, state: &mut MonitorState, event: MonitorEvent) {
+        let max_events = self.options.max_events;
+        if max_events == 0 {
+            return;
+        }
+
+        if state.events.len() >= max_events {
+            let excess = state.events.len() + 1 - max_events;
+            state.events.drain(..excess);
+        }
+        state.events.push(event);
+    }
+
+    /// Observes a sent `message`, so that replies to it are not considered unsolicited.
+    pub fn observe_sent(&self, message: &ArpMessage) {
+        if message.operation == Operation::ArpRequest {
+            let mut state = self.state.lock().unwrap();
+            let reply_window = self.options.reply_window;
+
+            state
+                .request_times
+                .retain(|_, request_time| request_time.elapsed() <= reply_window);
+            state
+                .request_times
+                .insert(message.target_protocol_address, Instant::now());
+        }
+    }
+
+    /// Returns all events raised since the last call and clears them.
+    pub fn take_events(&self) -> Vec<MonitorEvent> {
+        std::mem::take(&mut self.state.lock().unwrap().events)
+    }
+
+    /// Returns the IPv4 to MAC address bindings observed so far.
+    pub fn bindings(&self) -> Vec<(Ipv4Addr, MacAddr)> {
+        let mut bindings = self
+            .state
+            .lock()
+            .unwrap()
+            .bindings
+            .iter()
+            .map(|(ip, mac)| (*ip, *mac))
+            .collect::<Vec<(Ipv4Addr, MacAddr)>>();
+        bindings.sort();
+
+        bindings
+    }
+
+    /// Forgets all observed bindings, requests and events.
+    pub fn clear(&self) {
+        *self.state.lock().unwrap() = Default::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{client, inject, OTHER_MAC, OWN_IP, OWN_MAC, PEER_IP, PEER_MAC};
+
+    fn reply(mac: MacAddr, ip: Ipv4Addr) -> ArpMessage {
+        ArpMessage::new_arp_response(mac, ip, OWN_MAC, OWN_IP)
+    }
+
+    #[test]
+    fn detects_changed_mac() {
+        let monitor = ArpMonitor::default();
+        monitor.observe(&ArpMessage::new_gratuitous_request(PEER_MAC, PEER_IP), None);
+        monitor.observe(
+            &ArpMessage::new_gratuitous_request(OTHER_MAC, PEER_IP),
+            None,
+        );
+
+        assert_eq!(
+            monitor.take_events(),
+            vec![MonitorEvent::MacChanged {
+                ip: PEER_IP,
+                old_mac: PEER_MAC,
+                new_mac: OTHER_MAC,
+            }]
+        );
+        assert_eq!(monitor.bindings(), vec![(PEER_IP, OTHER_MAC)]);
+    }
+
+    #[test]
+    fn detects_many_addresses() {
+        let monitor = ArpMonitor::new(MonitorOptions {
+            max_addresses_per_mac: 2,
+            ..Default::default()
+        });
+        let ips = [Ipv4Addr::new(10, 0, 0, 3), Ipv4Addr::new(10, 0, 0, 2)];
+        for ip in ips {
+            monitor.observe(&ArpMessage::new_gratuitous_request(OTHER_MAC, ip), None);
+        }
+
+        assert_eq!(
+            monitor.take_events(),
+            vec![MonitorEvent::ManyAddresses {
+                mac: OTHER_MAC,
+                ips: vec![Ipv4Addr::new(10, 0, 0, 2), Ipv4Addr::new(10, 0, 0, 3)],
+            }]
+        );
+    }
+
+    #[test]
+    fn detects_source_mismatch() {
+        let monitor = ArpMonitor::default();
+        monitor.observe(
+            &ArpMessage::new_gratuitous_request(PEER_MAC, PEER_IP),
+            Some(OTHER_MAC),
+        );
+
+        assert_eq!(
+            monitor.take_events(),
+            vec![MonitorEvent::SourceMismatch {
+                ip: PEER_IP,
+                sender_mac: PEER_MAC,
+                ethernet_source: OTHER_MAC,
+            }]
+        );
+    }
+
+    #[test]
+    fn detects_unsolicited_replies() {
+        let monitor = ArpMonitor::default();
+        monitor.observe(&reply(OTHER_MAC, PEER_IP), None);
+        assert_eq!(
+            monitor.take_events(),
+            vec![MonitorEvent::UnsolicitedReply {
+                ip: PEER_IP,
+                mac: OTHER_MAC,
+            }]
+        );
+
+        monitor.observe_sent(&ArpMessage::new_arp_request(OWN_MAC, OWN_IP, PEER_IP));
+        monitor.observe(&reply(OTHER_MAC, PEER_IP), None);
+        assert!(monitor.take_events().is_empty());
+    }
+
+    #[test]
+    fn own_messages_are_not_unsolicited() {
+        let monitor = ArpMonitor::default();
+
+        // a request sent by the operating system makes the reply to it solicited
+        monitor.observe_own(&ArpMessage::new_arp_request(OWN_MAC, OWN_IP, PEER_IP), None);
+        monitor.observe(&reply(PEER_MAC, PEER_IP), None);
+
+        // replies sent by the operating system are not unsolicited
+        monitor.observe_own(
+            &ArpMessage::new_arp_response(OWN_MAC, OWN_IP, PEER_MAC, PEER_IP),
+            None,
+        );
+
+        assert!(monitor.take_events().is_empty());
+        assert_eq!(
+            monitor.bindings(),
+            vec![(OWN_IP, OWN_MAC), (PEER_IP, PEER_MAC)]
+        );
+    }
+
+    #[test]
+    fn discards_oldest_events() {
+        let monitor = ArpMonitor::new(MonitorOptions {
+            max_events: 2,
+            ..Default::default()
+        });
+        let ips = [1, 2, 3].map(|host| Ipv4Addr::new(10, 0, 1, host));
+        for ip in ips {
+            monitor.observe(&reply(OTHER_MAC, ip), None);
+        }
+
+        assert_eq!(
+            monitor.take_events(),
+            vec![
+                MonitorEvent::UnsolicitedReply {
+                    ip: ips[1],
+                    mac: OTHER_MAC,
+                },
+                MonitorEvent::UnsolicitedReply {
+                    ip: ips[2],
+                    mac: OTHER_MAC,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn client_treats_own_frames_as_sent() {
+        let (mut client, transport) = client();
+        let monitor = ArpMonitor::default();
+        client.set_monitor(monitor.clone());
+
+        let kernel_request = ArpMessage::new_arp_request(OWN_MAC, OWN_IP, PEER_IP);
+        inject(&transport, &kernel_request);
+        inject(&transport, &reply(PEER_MAC, PEER_IP));
+
+        for _ in 0..2 {
+            async_io::block_on(client.receive(Some(Duration::from_millis(100)))).unwrap();
+        }
+
+        assert!(monitor.take_events().is_empty());
+    }
+}
@@ -0,0 +1,179 @@
+use crate::{
+    arp::{ArpMessage, Operation},
+    client::{blocking, r#async::deadline_after, ArpClient},
+    error::Error,
+    interfaces::MacAddr,
+    transport::Transport,
+};
+use std::{
+    net::Ipv4Addr,
+    time::{Duration, Instant},
+};
+
+/// A reply to a request sent by `ArpClient::ping`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PingReply {
+    /// Index of the request answered, starting at 0.
+    pub sequence: usize,
+    /// Sender hardware address of the reply.
+    pub mac: MacAddr,
+    /// Time between sending the request and dequeuing the reply, see `ReceivedMessage::received_at`.
+    pub rtt: Duration,
+    /// Whether the request has already been answered before, e.g. by another host claiming the address.
+    pub duplicate: bool,
+}
+
+/// Statistics of a run of `ArpClient::ping`, similar to the summary of `arping`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PingStatistics {
+    /// Number of requests sent.
+    pub transmitted: usize,
+    /// Number of requests answered at least once.
+    pub received: usize,
+    /// All replies received, in order of arrival.
+    pub replies: Vec<PingReply>,
+}
+
+impl PingStatistics {
+    /// Returns the fraction of requests that have not been answered, between 0 and 1.
+    pub fn loss(&self) -> f64 {
+        if self.transmitted == 0 {
+            return 0.0;
+        }
+
+        1.0 - self.received as f64 / self.transmitted as f64
+    }
+
+    /// Returns the minimum round-trip time, or None if no request has been answered.
+    /// Like all RTT statistics, only considers the first reply to each request.
+    pub fn min_rtt(&self) -> Option<Duration> {
+        self.rtts().min()
+    }
+
+    /// Returns the average round-trip time, or None if no request has been answered.
+    pub fn avg_rtt(&self) -> Option<Duration> {
+        let count = self.rtts().count() as u32;
+        if count == 0 {
+            return None;
+        }
+
+        Some(self.rtts().sum::<Duration>() / count)
+    }
+
+    /// Returns the maximum round-trip time, or None if no request has been answered.
+    pub fn max_rtt(&self) -> Option<Duration> {
+        self.rtts().max()
+    }
+
+    /// Returns the standard deviation of the round-trip times, or None if no request has been answered.
+    pub fn stddev_rtt(&self) -> Option<Duration> {
+        let avg = self.avg_rtt()?.as_secs_f64();
+        let count = self.rtts().count() as f64;
+        let variance = self
+            .rtts()
+            .map(|rtt| (rtt.as_secs_f64() - avg).powi(2))
+            .sum::<f64>()
+            / count;
+
+        Some(Duration::from_secs_f64(variance.sqrt()))
+    }
+
+    /// Returns all MAC addresses that answered, ordered and without duplicates.
+    /// More than one address usually means that the IPv4 address is in use by multiple hosts.
+    pub fn macs(&self) -> Vec<MacAddr> {
+        let mut macs = self
+            .replies
+            .iter()
+            .map(|reply| reply.mac)
+            .collect::<Vec<MacAddr>>();
+        macs.sort();
+        macs.dedup();
+
+        macs
+    }
+
+    fn rtts(&self) -> impl Iterator<Item = Duration> + '_ {
+        self.replies
+            .iter()
+            .filter(|reply| !reply.duplicate)
+            .map(|reply| reply.rtt)
+    }
+
+    /// Records `message` received at `receive_time` if it is a reply from `ip_addr` to the latest request,
+    /// sent at `send_time`.
+    fn handle_message(
+        &mut self,
+        message: &ArpMessage,
+        ip_addr: Ipv4Addr,
+        send_time: Instant,
+        receive_time: Instant,
+    ) {
+        if message.operation != Operation::ArpResponse || message.source_protocol_address != ip_addr
+        {
+            return;
+        }
+
+        let sequence = self.transmitted - 1;
+        let duplicate = self.replies.iter().any(|reply| reply.sequence == sequence);
+        if !duplicate {
+            self.received += 1;
+        }
+
+        self.replies.push(PingReply {
+            sequence,
+            mac: message.source_hardware_address,
+            rtt: receive_time.saturating_duration_since(send_time),
+            duplicate,
+        });
+    }
+}
+
+impl<T: Transport> ArpClient<T> {
+    /// Checks whether `ip_addr` is reachable by sending `count` ARP requests to it, one every `interval`,
+    /// similar to `arping`. Replies to a request are accepted until the next request is sent,
+    /// or until `interval` has passed after the last request.
+    /// Works for hosts that do not answer ICMP echo requests, as long as they are on the local network.
+    pub async fn ping(
+        &mut self,
+        ip_addr: Ipv4Addr,
+        count: usize,
+        interval: Duration,
+    ) -> Result<PingStatistics, Error> {
+        let request = ArpMessage::new_arp_request(
+            self.get_interface().get_mac()?,
+            self.get_interface().get_ip()?,
+            ip_addr,
+        );
+        let mut statistics = PingStatistics::default();
+
+        for _ in 0..count {
+            let send_time = Instant::now();
+            self.send(&request).await?;
+            statistics.transmitted += 1;
+
+            let deadline = deadline_after(Some(interval));
+            while let Some(received) = self.receive_until(deadline).await? {
+                statistics.handle_message(
+                    &received.message,
+                    ip_addr,
+                    send_time,
+                    received.received_at,
+                );
+            }
+        }
+
+        Ok(statistics)
+    }
+}
+
+impl<T: Transport> blocking::ArpClient<T> {
+    /// Checks whether `ip_addr` is reachable by sending `count` ARP requests to it, see `ArpClient::ping`.
+    pub fn ping(
+        &mut self,
+        ip_addr: Ipv4Addr,
+        count: usize,
+        interval: Duration,
+    ) -> Result<PingStatistics, Error> {
+        async_io::block_on(self.as_async().ping(ip_addr, count, interval))
+    }
+}
@@ -0,0 +1,211 @@
+use crate::{
+    arp::{ArpMessage, Operation},
+    client::{blocking, r#async::deadline_after, ArpClient},
+    error::Error,
+    interfaces::MacAddr,
+    transport::Transport,
+};
+use rand::Rng;
+use std::{net::Ipv4Addr, time::Duration};
+
+/// Maximum initial random delay before the first probe (RFC 5227).
+pub const PROBE_WAIT: Duration = Duration::from_secs(1);
+/// Number of probes sent (RFC 5227).
+pub const PROBE_NUM: usize = 3;
+/// Minimum delay between probes (RFC 5227).
+pub const PROBE_MIN: Duration = Duration::from_secs(1);
+/// Maximum delay between probes (RFC 5227).
+pub const PROBE_MAX: Duration = Duration::from_secs(2);
+/// Delay after the last probe before the address is considered free (RFC 5227).
+pub const ANNOUNCE_WAIT: Duration = Duration::from_secs(2);
+
+/// Timing of the probe sequence of `ArpClient::probe_address_with_options`.
+/// Defaults to the values of RFC 5227.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProbeOptions {
+    /// Maximum initial random delay before the first probe.
+    pub probe_wait: Duration,
+    /// Number of probes sent.
+    pub probe_num: usize,
+    /// Minimum delay between probes.
+    pub probe_min: Duration,
+    /// Maximum delay between probes.
+    pub probe_max: Duration,
+    /// Delay after the last probe before the address is considered free.
+    pub announce_wait: Duration,
+}
+
+impl Default for ProbeOptions {
+    fn default() -> Self {
+        ProbeOptions {
+            probe_wait: PROBE_WAIT,
+            probe_num: PROBE_NUM,
+            probe_min: PROBE_MIN,
+            probe_max: PROBE_MAX,
+            announce_wait: ANNOUNCE_WAIT,
+        }
+    }
+}
+
+/// Outcome of probing an address.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ProbeResult {
+    /// No other host claimed the address.
+    Free,
+    /// The host with the contained MAC address claimed the address, or is probing for it as well.
+    Conflict(MacAddr),
+}
+
+/// Returns the MAC address of the conflicting host if `message` conflicts with probing for `ip_addr` from `own_mac`.
+/// Conflicts are messages from other hosts that either use `ip_addr` as sender address or probe for `ip_addr` themselves.
+fn conflicting_mac(message: &ArpMessage, ip_addr: Ipv4Addr, own_mac: MacAddr) -> Option<MacAddr> {
+    if message.source_hardware_address == own_mac {
+        return None;
+    }
+
+    let is_arp =
+        message.operation == Operation::ArpRequest || message.operation == Operation::ArpResponse;
+    let claims_address = message.source_protocol_address == ip_addr;
+    let probes_address = message.operation == Operation::ArpRequest
+        && message.source_protocol_address.is_unspecified()
+        && message.target_protocol_address == ip_addr;
+
+    if is_arp && (claims_address || probes_address) {
+        Some(message.source_hardware_address)
+    } else {
+        None
+    }
+}
+
+fn random_duration(min: Duration, max: Duration) -> Duration {
+    if min >= max {
+        return min;
+    }
+
+    rand::thread_rng().gen_range(min..=max)
+}
+
+impl<T: Transport> ArpClient<T> {
+    /// Checks whether `ip_addr` is in use on the network, using the probe sequence of RFC 5227
+    /// with its default timing. Takes between 4 and 7 seconds if the address is free.
+    pub async fn probe_address(&mut self, ip_addr: Ipv4Addr) -> Result<ProbeResult, Error> {
+        self.probe_address_with_options(ip_addr, &ProbeOptions::default())
+            .await
+    }
+
+    /// Checks whether `ip_addr` is in use on the network, using the probe sequence of RFC 5227 with the timing in `options`.
+    /// Returns as soon as a conflicting reply or probe is received.
+    pub async fn probe_address_with_options(
+        &mut self,
+        ip_addr: Ipv4Addr,
+        options: &ProbeOptions,
+    ) -> Result<ProbeResult, Error> {
+        let own_mac = self.get_interface().get_mac()?;
+        let probe = ArpMessage::new_arp_probe(own_mac, ip_addr);
+
+        let initial_wait = random_duration(Duration::ZERO, options.probe_wait);
+        if let Some(mac) = self
+            .listen_for_conflict(ip_addr, own_mac, initial_wait)
+            .await?
+        {
+            return Ok(ProbeResult::Conflict(mac));
+        }
+
+        for i in 0..options.probe_num {
+            self.send(&probe).await?;
+
+            let wait = if i + 1 == options.probe_num {
+                options.announce_wait
+            } else {
+                random_duration(options.probe_min, options.probe_max)
+            };
+
+            if let Some(mac) = self.listen_for_conflict(ip_addr, own_mac, wait).await? {
+                return Ok(ProbeResult::Conflict(mac));
+            }
+        }
+
+        Ok(ProbeResult::Free)
+    }
+
+    async fn listen_for_conflict(
+        &mut self,
+        ip_addr: Ipv4Addr,
+        own_mac: MacAddr,
+        duration: Duration,
+    ) -> Result<Option<MacAddr>, Error> {
+        let deadline = deadline_after(Some(duration));
+        while let Some(received) = self.receive_until(deadline).await? {
+            if let Some(mac) = conflicting_mac(&received.message, ip_addr, own_mac) {
+                return Ok(Some(mac));
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+impl<T: Transport> blocking::ArpClient<T> {
+    /// Checks whether `ip_addr` is in use on the network, see `ArpClient::probe_address`.
+    pub fn probe_address(&mut self, ip_addr: Ipv4Addr) -> Result<ProbeResult, Error> {
+        async_io::block_on(self.as_async().probe_address(ip_addr))
+    }
+
+    /// Checks whether `ip_addr` is in use on the network with the timing in `options`,
+    /// see `ArpClient::probe_address_with_options`.
+    pub fn probe_address_with_options(
+        &mut self,
+        ip_addr: Ipv4Addr,
+        options: &ProbeOptions,
+    ) -> Result<ProbeResult, Error> {
+        async_io::block_on(self.as_async().probe_address_with_options(ip_addr, options))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test_util::{OWN_MAC, PEER_IP, PEER_MAC};
+
+    const PROBED_IP: Ipv4Addr = Ipv4Addr::new(10, 0, 0, 5);
+
+    #[test]
+    fn host_claiming_address_conflicts() {
+        let reply =
+            ArpMessage::new_arp_response(PEER_MAC, PROBED_IP, OWN_MAC, Ipv4Addr::UNSPECIFIED);
+        let announcement = ArpMessage::new_gratuitous_request(PEER_MAC, PROBED_IP);
+
+        assert_eq!(conflicting_mac(&reply, PROBED_IP, OWN_MAC), Some(PEER_MAC));
+        assert_eq!(
+            conflicting_mac(&announcement, PROBED_IP, OWN_MAC),
+            Some(PEER_MAC)
+        );
+    }
+
+    #[test]
+    fn host_probing_for_address_conflicts() {
+        let probe = ArpMessage::new_arp_probe(PEER_MAC, PROBED_IP);
+
+        assert_eq!(conflicting_mac(&probe, PROBED_IP, OWN_MAC), Some(PEER_MAC));
+    }
+
+    #[test]
+    fn own_probe_does_not_conflict() {
+        let probe = ArpMessage::new_arp_probe(OWN_MAC, PROBED_IP);
+
+        assert_eq!(conflicting_mac(&probe, PROBED_IP, OWN_MAC), None);
+    }
+
+    #[test]
+    fn other_traffic_does_not_conflict() {
+        // a host looking up the address does not use it
+        let request = ArpMessage::new_arp_request(PEER_MAC, PEER_IP, PROBED_IP);
+        let probe = ArpMessage::new_arp_probe(PEER_MAC, PEER_IP);
+        let rarp = ArpMessage::new_rarp_response(PEER_MAC, PROBED_IP, OWN_MAC, PEER_IP);
+
+        assert_eq!(conflicting_mac(&request, PROBED_IP, OWN_MAC), None);
+        assert_eq!(conflicting_mac(&probe, PROBED_IP, OWN_MAC), None);
+        assert_eq!(conflicting_mac(&rarp, PROBED_IP, OWN_MAC), None);
+    }
+}
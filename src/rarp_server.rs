@@ -0,0 +1,254 @@
+use crate::{
+    arp::{ArpMessage, Operation},
+    client::{blocking, ArpClient},
+    error::Error,
+    interfaces::MacAddr,
+    transport::Transport,
+};
+use std::{
+    collections::HashMap,
+    fs,
+    net::{IpAddr, Ipv4Addr, ToSocketAddrs},
+    path::Path,
+};
+
+/// Answers RARP requests using a table mapping MAC addresses to IPv4 addresses.
+/// Use it with `ArpClient::respond_rarp_next` or `ArpClient::run_rarp_server`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RarpServer {
+    mappings: HashMap<MacAddr, Ipv4Addr>,
+}
+
+impl RarpServer {
+    /// Creates a server with an empty mapping table.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Creates a server with the mappings of the `/etc/ethers`-style file at `path`.
+    /// See `parse_ethers` for the format.
+    pub fn from_ethers_file<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let mut server = RarpServer::new();
+        server.parse_ethers(&fs::read_to_string(path)?)?;
+
+        Ok(server)
+    }
+
+    /// Adds the mappings in `contents`, formatted like `/etc/ethers`: every line consists of a MAC address
+    /// and an IPv4 address or host name, separated by whitespace. `#` starts a comment.
+    /// Host names are resolved to their first IPv4 address.
+    /// # Errors
+    /// Returns an error for the first line that can not be parsed or resolved.
+    pub fn parse_ethers(&mut self, contents: &str) -> Result<(), Error> {
+        for (line_number, line) in contents.lines().enumerate() {
+            let line = match line.find('#') {
+                Some(comment_start) => &line[..comment_start],
+                None => line,
+            };
+
+            let mut fields = line.split_whitespace();
+            let (mac, host) = match (fields.next(), fields.next()) {
+                (Some(mac), Some(host)) => (mac, host),
+                (None, _) => continue,
+                (Some(_), None) => {
+                    return Err(Error::InvalidInput {
+                        reason: format!("Missing address in line {}.", line_number + 1),
+                    })
+                }
+            };
+
+            self.insert(mac.parse()?, RarpServer::resolve_host(host)?);
+        }
+
+        Ok(())
+    }
+
+    /// Maps `mac_addr` to `ip_addr`, replacing any previous mapping of `mac_addr`.
+    pub fn insert(&mut self, mac_addr: MacAddr, ip_addr: Ipv4Addr) {
+        self.mappings.insert(mac_addr, ip_addr);
+    }
+
+    /// Removes the mapping of `mac_addr`.
+    pub fn remove(&mut self, mac_addr: MacAddr) {
+        self.mappings.remove(&mac_addr);
+    }
+
+    /// Returns the IPv4 address `mac_addr` is mapped to.
+    pub fn get(&self, mac_addr: MacAddr) -> Option<Ipv4Addr> {
+        self.mappings.get(&mac_addr).copied()
+    }
+
+    /// Returns the response to `message` if it is a RARP request for a MAC address in the mapping table.
+    /// The response is sent from `server_mac` and `server_ip`, on the VLAN of `message`.
+    pub fn respond(
+        &self,
+        message: &ArpMessage,
+        server_mac: MacAddr,
+        server_ip: Ipv4Addr,
+    ) -> Option<ArpMessage> {
+        if message.operation != Operation::RarpRequest {
+            return None;
+        }
+
+        let ip_addr = self.get(message.target_hardware_address)?;
+
+        Some(ArpMessage {
+            vlan_tags: message.vlan_tags.clone(),
+            ..ArpMessage::new_rarp_response(
+                server_mac,
+                server_ip,
+                message.target_hardware_address,
+                ip_addr,
+            )
+        })
+    }
+
+    fn resolve_host(host: &str) -> Result<Ipv4Addr, Error> {
+        if let Ok(ip_addr) = host.parse::<Ipv4Addr>() {
+            return Ok(ip_addr);
+        }
+
+        let ip_addr = (host, 0)
+            .to_socket_addrs()?
+            .find_map(|socket_addr| match socket_addr.ip() {
+                IpAddr::V4(ip_addr) => Some(ip_addr),
+                _ => None,
+            });
+
+        match ip_addr {
+            Some(ip_addr) => Ok(ip_addr),
+            None => Err(Error::InvalidInput {
+                reason: format!("Host {} has no IPv4 address.", host),
+            }),
+        }
+    }
+}
+
+impl<T: Transport> ArpClient<T> {
+    /// Receives the next message and answers it using `server`, if applicable.
+    /// Returns the response sent, or None if the received frame was not answered.
+    ///
+    /// # Errors
+    /// Returns an error if receiving the message or sending the response fails.
+    pub async fn respond_rarp_next(
+        &mut self,
+        server: &RarpServer,
+    ) -> Result<Option<ArpMessage>, Error> {
+        let server_mac = self.get_interface().get_mac()?;
+        let server_ip = self.get_interface().get_ip()?;
+
+        let message = self.receive(None).await?.message;
+        let response = server.respond(&message, server_mac, server_ip);
+
+        if let Some(response) = &response {
+            self.send(response).await?;
+        }
+
+        Ok(response)
+    }
+
+    /// Answers incoming RARP requests using `server`. Only returns if receiving a message or sending a response fails.
+    pub async fn run_rarp_server(&mut self, server: &RarpServer) -> Result<(), Error> {
+        loop {
+            self.respond_rarp_next(server).await?;
+        }
+    }
+}
+
+impl<T: Transport> blocking::ArpClient<T> {
+    /// Receives the next message and answers it using `server`, see `ArpClient::respond_rarp_next`.
+    pub fn respond_rarp_next(&mut self, server: &RarpServer) -> Result<Option<ArpMessage>, Error> {
+        async_io::block_on(self.as_async().respond_rarp_next(server))
+    }
+
+    /// Answers incoming RARP requests using `server`. Only returns if receiving a message or sending a response fails.
+    pub fn run_rarp_server(&mut self, server: &RarpServer) -> Result<(), Error> {
+        async_io::block_on(self.as_async().run_rarp_server(server))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{
+        client, inject, sent_messages, OWN_IP as SERVER_IP, OWN_MAC as SERVER_MAC,
+        PEER_IP as CLIENT_IP, PEER_MAC as CLIENT_MAC,
+    };
+
+    #[test]
+    fn parses_ethers_with_comments_and_blank_lines() {
+        let mut server = RarpServer::new();
+        let contents = "# static assignments\n\
+                        \n\
+                        02:00:00:00:00:02 10.0.0.2 # client\n\
+                        \t02:00:00:00:00:03\t10.0.0.3\n";
+
+        server.parse_ethers(contents).unwrap();
+
+        assert_eq!(server.get(CLIENT_MAC), Some(CLIENT_IP));
+        assert_eq!(
+            server.get(MacAddr(0x02, 0, 0, 0, 0, 3)),
+            Some(Ipv4Addr::new(10, 0, 0, 3))
+        );
+        assert_eq!(server.mappings.len(), 2);
+    }
+
+    #[test]
+    fn reports_line_of_missing_address() {
+        let mut server = RarpServer::new();
+
+        match server.parse_ethers("02:00:00:00:00:02 10.0.0.2\n02:00:00:00:00:03 # none\n") {
+            Err(Error::InvalidInput { reason }) => assert!(reason.contains("line 2"), "{}", reason),
+            result => panic!("unexpected result {:?}", result),
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_mac() {
+        let mut server = RarpServer::new();
+
+        let result = server.parse_ethers("02:00:00:00:00 10.0.0.2\n");
+        assert!(matches!(result, Err(Error::InvalidInput { .. })));
+    }
+
+    #[test]
+    fn answers_only_known_macs() {
+        let mut server = RarpServer::new();
+        server.insert(CLIENT_MAC, CLIENT_IP);
+
+        let response = server
+            .respond(
+                &ArpMessage::new_rarp_request(CLIENT_MAC, CLIENT_MAC),
+                SERVER_MAC,
+                SERVER_IP,
+            )
+            .unwrap();
+        assert_eq!(response.operation, Operation::RarpResponse);
+        assert_eq!(response.source_hardware_address, SERVER_MAC);
+        assert_eq!(response.source_protocol_address, SERVER_IP);
+        assert_eq!(response.target_hardware_address, CLIENT_MAC);
+        assert_eq!(response.target_protocol_address, CLIENT_IP);
+
+        let unknown = ArpMessage::new_rarp_request(CLIENT_MAC, MacAddr(0x02, 0, 0, 0, 0, 9));
+        assert!(server.respond(&unknown, SERVER_MAC, SERVER_IP).is_none());
+    }
+
+    #[test]
+    fn respond_rarp_next_answers_request() {
+        let (mut client, transport) = client();
+        let mut server = RarpServer::new();
+        server.insert(CLIENT_MAC, CLIENT_IP);
+
+        let request = ArpMessage::new_rarp_request(CLIENT_MAC, CLIENT_MAC);
+        inject(&transport, &request);
+        let response = async_io::block_on(client.respond_rarp_next(&server)).unwrap();
+        assert!(response.is_some());
+
+        let sent = sent_messages(&transport);
+        assert_eq!(sent.len(), 1);
+        let sent = &sent[0];
+        assert_eq!(sent.operation, Operation::RarpResponse);
+        assert_eq!(sent.target_protocol_address, CLIENT_IP);
+        assert_eq!(sent.get_ethernet_destination(), CLIENT_MAC);
+    }
+}
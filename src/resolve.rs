@@ -0,0 +1,344 @@
+use crate::{
+    arp::ArpMessage,
+    client::{
+        blocking,
+        pending::PendingWait,
+        r#async::{deadline_after, reply_addresses},
+        ArpClient, DuplicateAddress,
+    },
+    error::Error,
+    interfaces::MacAddr,
+    transport::Transport,
+};
+use std::{
+    collections::{HashMap, HashSet},
+    net::Ipv4Addr,
+    time::{Duration, Instant},
+};
+
+/// Options for resolving many addresses at once with `ArpClient::resolve_many_with_options`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ResolveOptions {
+    /// Maximum number of ARP requests sent per second.
+    pub rate: u32,
+    /// Time to wait for replies after the last request of a round has been sent.
+    pub timeout: Duration,
+    /// Number of additional rounds requesting the addresses that did not answer.
+    pub retries: usize,
+}
+
+impl Default for ResolveOptions {
+    fn default() -> Self {
+        ResolveOptions {
+            rate: 500,
+            timeout: Duration::from_secs(1),
+            retries: 0,
+        }
+    }
+}
+
+impl<T: Transport> ArpClient<T> {
+    /// Resolves all of `ip_addrs` to MAC addresses at once, sending 500 requests per second at most.
+    /// Collects the replies until `timeout` has passed after the last request, and requests the addresses
+    /// that did not answer again up to `retries` times. See `resolve_many_with_options`.
+    pub async fn resolve_many(
+        &mut self,
+        ip_addrs: &[Ipv4Addr],
+        timeout: Duration,
+        retries: usize,
+    ) -> Result<HashMap<Ipv4Addr, Result<MacAddr, Error>>, Error> {
+        let options = ResolveOptions {
+            timeout,
+            retries,
+            ..Default::default()
+        };
+
+        self.resolve_many_with_options(ip_addrs, &options).await
+    }
+
+    /// Resolves all of `ip_addrs` to MAC addresses at once. Sends a request for every address, paced to
+    /// `options.rate` requests per second, and collects the replies until `options.timeout` has passed after
+    /// the last request. Addresses that did not answer are requested again, up to `options.retries` times.
+    ///
+    /// Returns the result for every address: its MAC address, `Error::Timeout` if it did not answer or
+    /// `Error::DuplicateAddress` if multiple hosts answered. If a cache is attached to the client, addresses
+    /// resolved by the cache are not requested, and the results are stored in the cache.
+    /// # Errors
+    /// Returns `Error::InvalidInput` if `options.rate` is zero, or an error if sending or receiving fails.
+    pub async fn resolve_many_with_options(
+        &mut self,
+        ip_addrs: &[Ipv4Addr],
+        options: &ResolveOptions,
+    ) -> Result<HashMap<Ipv4Addr, Result<MacAddr, Error>>, Error> {
+        if options.rate == 0 {
+            return Err(Error::InvalidInput {
+                reason: "Resolve rate must be greater than zero.".to_string(),
+            });
+        }
+
+        let own_mac = self.get_interface().get_mac()?;
+        let own_ip = self.get_interface().get_ip()?;
+        let cache = self.get_cache().cloned();
+
+        let mut results = HashMap::new();
+        let mut unresolved = Vec::new();
+        let mut seen = HashSet::new();
+        for &ip_addr in ip_addrs {
+            match cache.as_ref().and_then(|cache| cache.get(ip_addr)) {
+                Some(mac_addr) => {
+                    results.insert(ip_addr, Ok(mac_addr));
+                }
+                None if seen.insert(ip_addr) => unresolved.push(ip_addr),
+                None => {}
+            }
+        }
+
+        for _ in 0..=options.retries {
+            if unresolved.is_empty() {
+                break;
+            }
+
+            // requests sent are no longer tracked once dropped, even if sending or receiving fails
+            let mut requests = Vec::new();
+            self.request_all(own_mac, own_ip, &unresolved, options, &mut requests)
+                .await?;
+
+            for (ip_addr, wait) in requests {
+                let replies = wait.finish();
+                let macs = reply_addresses(&replies, |reply| reply.source_hardware_address);
+                match macs.len() {
+                    0 => continue,
+                    1 => results.insert(ip_addr, Ok(macs[0])),
+                    _ => {
+                        results.insert(ip_addr, Err(DuplicateAddress { ip: ip_addr, macs }.into()))
+                    }
+                };
+            }
+
+            unresolved.retain(|ip_addr| !results.contains_key(ip_addr));
+        }
+
+        for ip_addr in unresolved {
+            results.insert(ip_addr, Err(Error::Timeout));
+        }
+
+        if let Some(cache) = &cache {
+            for (ip_addr, result) in &results {
+                match result {
+                    Ok(mac_addr) => cache.insert(*ip_addr, *mac_addr),
+                    Err(Error::Timeout) => cache.insert_unreachable(*ip_addr),
+                    Err(_) => cache.remove(*ip_addr),
+                }
+            }
+        }
+
+        Ok(results)
+    }
+
+    /// Sends a request for each of `ip_addrs`, paced to `options.rate`, and receives messages until
+    /// `options.timeout` has passed after the last request. Each request sent is added to `requests`.
+    async fn request_all(
+        &mut self,
+        own_mac: MacAddr,
+        own_ip: Ipv4Addr,
+        ip_addrs: &[Ipv4Addr],
+        options: &ResolveOptions,
+        requests: &mut Vec<(Ipv4Addr, PendingWait)>,
+    ) -> Result<(), Error> {
+        let send_interval = Duration::from_secs(1) / options.rate;
+        let mut next_send_time = Instant::now();
+
+        for &ip_addr in ip_addrs {
+            // replies received while waiting are kept for their requests
+            while self.receive_until(Some(next_send_time)).await?.is_some() {}
+
+            let request = ArpMessage::new_arp_request(own_mac, own_ip, ip_addr);
+            let id = self.send_request(&request).await?;
+            requests.push((ip_addr, self.wait_request(id)));
+            next_send_time = Instant::now() + send_interval;
+        }
+
+        let deadline = deadline_after(Some(options.timeout));
+        while self.receive_until(deadline).await?.is_some() {}
+
+        Ok(())
+    }
+}
+
+impl<T: Transport> blocking::ArpClient<T> {
+    /// Resolves all of `ip_addrs` to MAC addresses at once, see `ArpClient::resolve_many`.
+    pub fn resolve_many(
+        &mut self,
+        ip_addrs: &[Ipv4Addr],
+        timeout: Duration,
+        retries: usize,
+    ) -> Result<HashMap<Ipv4Addr, Result<MacAddr, Error>>, Error> {
+        async_io::block_on(self.as_async().resolve_many(ip_addrs, timeout, retries))
+    }
+
+    /// Resolves all of `ip_addrs` to MAC addresses at once with `options`, see `ArpClient::resolve_many_with_options`.
+    pub fn resolve_many_with_options(
+        &mut self,
+        ip_addrs: &[Ipv4Addr],
+        options: &ResolveOptions,
+    ) -> Result<HashMap<Ipv4Addr, Result<MacAddr, Error>>, Error> {
+        async_io::block_on(self.as_async().resolve_many_with_options(ip_addrs, options))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        cache::{ArpCache, CacheLookup},
+        test_util::{client, inject, sent_messages, OWN_IP, OWN_MAC},
+        transport::MemoryTransport,
+    };
+    use std::{
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc,
+        },
+        thread::{self, JoinHandle},
+    };
+
+    fn host(host: u8) -> Ipv4Addr {
+        Ipv4Addr::new(10, 0, 0, host)
+    }
+
+    fn mac(host: u8) -> MacAddr {
+        MacAddr(0x02, 0, 0, 0, 0, host)
+    }
+
+    /// Simulates the hosts on the network: `answer` returns the MAC addresses answering a request for an address,
+    /// given the number of requests for it received before. Returns the number of requests received per address.
+    struct Peers {
+        stop: Arc<AtomicBool>,
+        thread: JoinHandle<HashMap<Ipv4Addr, usize>>,
+    }
+
+    impl Peers {
+        fn spawn(
+            transport: MemoryTransport,
+            answer: impl Fn(Ipv4Addr, usize) -> Vec<MacAddr> + Send + 'static,
+        ) -> Self {
+            let stop = Arc::new(AtomicBool::new(false));
+            let thread_stop = stop.clone();
+
+            let thread = thread::spawn(move || {
+                let mut requests = HashMap::new();
+
+                while !thread_stop.load(Ordering::SeqCst) {
+                    for request in sent_messages(&transport) {
+                        let ip_addr = request.target_protocol_address;
+                        let count = requests.entry(ip_addr).or_insert(0);
+
+                        for mac_addr in answer(ip_addr, *count) {
+                            let reply =
+                                ArpMessage::new_arp_response(mac_addr, ip_addr, OWN_MAC, OWN_IP);
+                            inject(&transport, &reply);
+                        }
+                        *count += 1;
+                    }
+
+                    thread::sleep(Duration::from_millis(1));
+                }
+
+                requests
+            });
+
+            Peers { stop, thread }
+        }
+
+        fn join(self) -> HashMap<Ipv4Addr, usize> {
+            self.stop.store(true, Ordering::SeqCst);
+            self.thread.join().unwrap()
+        }
+    }
+
+    fn options(retries: usize) -> ResolveOptions {
+        ResolveOptions {
+            timeout: Duration::from_millis(50),
+            retries,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn resolves_answered_silent_and_duplicate() {
+        let (mut client, transport) = client();
+        let peers = Peers::spawn(transport, |ip_addr, _| match ip_addr.octets()[3] {
+            2 => vec![mac(2)],
+            4 => vec![mac(4), mac(5)],
+            _ => Vec::new(),
+        });
+
+        let results = async_io::block_on(
+            client.resolve_many_with_options(&[host(2), host(3), host(4), host(2)], &options(0)),
+        )
+        .unwrap();
+        let requests = peers.join();
+
+        assert_eq!(results.len(), 3);
+        assert!(matches!(results[&host(2)], Ok(mac_addr) if mac_addr == mac(2)));
+        assert!(matches!(results[&host(3)], Err(Error::Timeout)));
+        match &results[&host(4)] {
+            Err(Error::DuplicateAddress(duplicate)) => {
+                assert_eq!(duplicate.macs, vec![mac(4), mac(5)])
+            }
+            result => panic!("unexpected result {:?}", result),
+        }
+        assert_eq!(requests[&host(2)], 1);
+    }
+
+    #[test]
+    fn retries_unanswered_addresses() {
+        let (mut client, transport) = client();
+        let peers = Peers::spawn(transport, |_, count| match count {
+            0 => Vec::new(),
+            _ => vec![mac(2)],
+        });
+
+        let results =
+            async_io::block_on(client.resolve_many_with_options(&[host(2)], &options(1))).unwrap();
+        let requests = peers.join();
+
+        assert!(matches!(results[&host(2)], Ok(mac_addr) if mac_addr == mac(2)));
+        assert_eq!(requests[&host(2)], 2);
+    }
+
+    #[test]
+    fn writes_results_to_cache() {
+        let (mut client, transport) = client();
+        let cache = ArpCache::default();
+        client.set_cache(cache.clone());
+        let peers = Peers::spawn(transport, |ip_addr, _| match ip_addr.octets()[3] {
+            2 => vec![mac(2)],
+            _ => Vec::new(),
+        });
+
+        async_io::block_on(client.resolve_many_with_options(&[host(2), host(3)], &options(0)))
+            .unwrap();
+        // resolved addresses are answered by the cache
+        let results =
+            async_io::block_on(client.resolve_many_with_options(&[host(2)], &options(0))).unwrap();
+        let requests = peers.join();
+
+        assert_eq!(cache.get(host(2)), Some(mac(2)));
+        assert_eq!(cache.lookup(host(3)), CacheLookup::Unreachable);
+        assert!(matches!(results[&host(2)], Ok(mac_addr) if mac_addr == mac(2)));
+        assert_eq!(requests[&host(2)], 1);
+    }
+
+    #[test]
+    fn rejects_zero_rate() {
+        let (mut client, _transport) = client();
+        let options = ResolveOptions {
+            rate: 0,
+            ..Default::default()
+        };
+
+        let result = async_io::block_on(client.resolve_many_with_options(&[host(2)], &options));
+        assert!(matches!(result, Err(Error::InvalidInput { .. })));
+    }
+}
@@ -0,0 +1,355 @@
+use crate::{
+    arp::{ArpMessage, Operation},
+    client::{blocking, ArpClient},
+    error::Error,
+    interfaces::MacAddr,
+    transport::Transport,
+};
+use pnet::ipnetwork::Ipv4Network;
+use std::net::Ipv4Addr;
+
+/// Answers ARP requests for a configurable set of IPv4 addresses and subnets, e.g. for proxy ARP.
+/// Use it with `ArpClient::respond_next` or `ArpClient::run_responder`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ArpResponder {
+    networks: Vec<Ipv4Network>,
+    mac: Option<MacAddr>,
+    inverse: bool,
+    inverse_addresses: Vec<Ipv4Addr>,
+}
+
+impl ArpResponder {
+    /// Creates a responder that does not answer for any address yet.
+    /// Answers contain the MAC address of the client's interface, unless changed with `set_mac`.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Answers requests for `ip_addr`.
+    pub fn add_address(&mut self, ip_addr: Ipv4Addr) {
+        self.networks.push(Ipv4Network::from(ip_addr));
+    }
+
+    /// Answers requests for every address in the subnet `network` with the prefix length `prefix`.
+    pub fn add_network(&mut self, network: Ipv4Addr, prefix: u8) -> Result<(), Error> {
+        match Ipv4Network::new(network, prefix) {
+            Ok(network) => {
+                self.networks.push(network);
+                Ok(())
+            }
+            Err(err) => Err(Error::InvalidInput {
+                reason: err.to_string(),
+            }),
+        }
+    }
+
+    /// Answers with `mac` instead of the MAC address of the client's interface.
+    pub fn set_mac(&mut self, mac: MacAddr) {
+        self.mac = Some(mac);
+    }
+
+    /// Enables or disables answering InARP requests (RFC 2390) addressed to the MAC address of the client's interface.
+    /// The MAC address set with `set_mac` is never answered for. Disabled by default.
+    pub fn set_inverse(&mut self, enabled: bool) {
+        self.inverse = enabled;
+    }
+
+    /// Reports `ip_addr` in answers to InARP requests. If no address is added,
+    /// the IPv4 address of the client's interface is reported.
+    pub fn add_inverse_address(&mut self, ip_addr: Ipv4Addr) {
+        self.inverse_addresses.push(ip_addr);
+    }
+
+    /// Returns whether the responder answers requests for `ip_addr`.
+    pub fn responds_for(&self, ip_addr: Ipv4Addr) -> bool {
+        self.networks
+            .iter()
+            .any(|network| network.contains(ip_addr))
+    }
+
+    /// Returns the response to `message` if it is an ARP request for an address the responder answers for.
+    /// `default_mac` is used in the response if no MAC address has been set.
+    /// Gratuitous requests, which announce the address of the sender, are never answered.
+    /// The response is sent on the VLAN of `message`.
+    pub fn respond(&self, message: &ArpMessage, default_mac: MacAddr) -> Option<ArpMessage> {
+        let is_gratuitous = message.source_protocol_address == message.target_protocol_address;
+
+        if message.operation != Operation::ArpRequest
+            || is_gratuitous
+            || !self.responds_for(message.target_protocol_address)
+        {
+            return None;
+        }
+
+        Some(ArpMessage {
+            vlan_tags: message.vlan_tags.clone(),
+            ..ArpMessage::new_arp_response(
+                self.mac.unwrap_or(default_mac),
+                message.target_protocol_address,
+                message.source_hardware_address,
+                message.source_protocol_address,
+            )
+        })
+    }
+
+    /// Returns the responses to `message` if it is an InARP request for `own_mac` and answering InARP requests is enabled.
+    /// One response is returned per address added with `add_inverse_address`, or for `default_ip` if none has been added.
+    /// The responses are sent on the VLAN of `message`.
+    pub fn respond_inverse(
+        &self,
+        message: &ArpMessage,
+        own_mac: MacAddr,
+        default_ip: Option<Ipv4Addr>,
+    ) -> Vec<ArpMessage> {
+        if !self.inverse
+            || message.operation != Operation::InArpRequest
+            || message.target_hardware_address != own_mac
+        {
+            return Vec::new();
+        }
+
+        let addresses = if self.inverse_addresses.is_empty() {
+            default_ip.into_iter().collect()
+        } else {
+            self.inverse_addresses.clone()
+        };
+
+        addresses
+            .into_iter()
+            .map(|ip_addr| ArpMessage {
+                vlan_tags: message.vlan_tags.clone(),
+                ..ArpMessage::new_inarp_response(
+                    own_mac,
+                    ip_addr,
+                    message.source_hardware_address,
+                    message.source_protocol_address,
+                )
+            })
+            .collect()
+    }
+}
+
+impl<T: Transport> ArpClient<T> {
+    /// Receives the next message and answers it using `responder`, if applicable.
+    /// Returns the responses sent, which are empty if the received frame was not answered.
+    ///
+    /// # Errors
+    /// Returns an error if receiving the message or sending the response fails.
+    pub async fn respond_next(
+        &mut self,
+        responder: &ArpResponder,
+    ) -> Result<Vec<ArpMessage>, Error> {
+        let own_mac = self.get_interface().get_mac()?;
+        let own_ip = self.get_interface().get_ip().ok();
+
+        let message = self.receive(None).await?.message;
+
+        // frames sent by the interface itself are never answered
+        let responses = if message.source_hardware_address == own_mac {
+            Vec::new()
+        } else {
+            match responder.respond(&message, own_mac) {
+                Some(response) => vec![response],
+                None => responder.respond_inverse(&message, own_mac, own_ip),
+            }
+        };
+
+        for response in &responses {
+            self.send(response).await?;
+        }
+
+        Ok(responses)
+    }
+
+    /// Answers incoming requests using `responder`. Only returns if receiving a message or sending a response fails.
+    pub async fn run_responder(&mut self, responder: &ArpResponder) -> Result<(), Error> {
+        loop {
+            self.respond_next(responder).await?;
+        }
+    }
+}
+
+impl<T: Transport> blocking::ArpClient<T> {
+    /// Receives the next message and answers it using `responder`, see `ArpClient::respond_next`.
+    pub fn respond_next(&mut self, responder: &ArpResponder) -> Result<Vec<ArpMessage>, Error> {
+        async_io::block_on(self.as_async().respond_next(responder))
+    }
+
+    /// Answers incoming requests using `responder`. Only returns if receiving a message or sending a response fails.
+    pub fn run_responder(&mut self, responder: &ArpResponder) -> Result<(), Error> {
+        async_io::block_on(self.as_async().run_responder(responder))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{
+        client, inject, sent_messages, OTHER_MAC as PROXY_MAC, OWN_IP, OWN_MAC, PEER_IP, PEER_MAC,
+    };
+    use crate::vlan::VlanTag;
+
+    fn proxy_responder() -> ArpResponder {
+        let mut responder = ArpResponder::new();
+        responder.add_address(Ipv4Addr::new(10, 0, 1, 1));
+        responder
+            .add_network(Ipv4Addr::new(10, 0, 2, 0), 24)
+            .unwrap();
+
+        responder
+    }
+
+    fn request_for(ip_addr: Ipv4Addr) -> ArpMessage {
+        ArpMessage::new_arp_request(PEER_MAC, PEER_IP, ip_addr)
+    }
+
+    #[test]
+    fn answers_configured_addresses_and_networks() {
+        let responder = proxy_responder();
+
+        assert!(responder
+            .respond(&request_for(Ipv4Addr::new(10, 0, 1, 1)), OWN_MAC)
+            .is_some());
+        assert!(responder
+            .respond(&request_for(Ipv4Addr::new(10, 0, 2, 200)), OWN_MAC)
+            .is_some());
+        assert!(responder
+            .respond(&request_for(Ipv4Addr::new(10, 0, 1, 2)), OWN_MAC)
+            .is_none());
+        assert!(responder
+            .respond(&request_for(Ipv4Addr::new(10, 0, 3, 1)), OWN_MAC)
+            .is_none());
+    }
+
+    #[test]
+    fn answers_requester_directly() {
+        let responder = proxy_responder();
+        let target = Ipv4Addr::new(10, 0, 1, 1);
+
+        let response = responder.respond(&request_for(target), OWN_MAC).unwrap();
+
+        assert_eq!(response.operation, Operation::ArpResponse);
+        assert_eq!(response.source_hardware_address, OWN_MAC);
+        assert_eq!(response.source_protocol_address, target);
+        assert_eq!(response.target_hardware_address, PEER_MAC);
+        assert_eq!(response.target_protocol_address, PEER_IP);
+        assert_eq!(response.get_ethernet_destination(), PEER_MAC);
+    }
+
+    #[test]
+    fn answers_with_configured_mac() {
+        let mut responder = proxy_responder();
+        responder.set_mac(PROXY_MAC);
+
+        let response = responder
+            .respond(&request_for(Ipv4Addr::new(10, 0, 1, 1)), OWN_MAC)
+            .unwrap();
+        assert_eq!(response.source_hardware_address, PROXY_MAC);
+    }
+
+    #[test]
+    fn ignores_gratuitous_requests_and_replies() {
+        let mut responder = proxy_responder();
+        responder.add_address(PEER_IP);
+        let reply = ArpMessage::new_arp_response(PEER_MAC, PEER_IP, OWN_MAC, OWN_IP);
+
+        assert!(responder
+            .respond(
+                &ArpMessage::new_gratuitous_request(PEER_MAC, PEER_IP),
+                OWN_MAC
+            )
+            .is_none());
+        assert!(responder.respond(&reply, OWN_MAC).is_none());
+    }
+
+    #[test]
+    fn answers_on_vlan_of_request() {
+        let responder = proxy_responder();
+        let request = ArpMessage {
+            vlan_tags: vec![VlanTag::new(42, 0)],
+            ..request_for(Ipv4Addr::new(10, 0, 1, 1))
+        };
+
+        let response = responder.respond(&request, OWN_MAC).unwrap();
+        assert_eq!(response.vlan_tags, vec![VlanTag::new(42, 0)]);
+    }
+
+    #[test]
+    fn respond_next_ignores_own_frames() {
+        let (mut client, transport) = client();
+        let responder = proxy_responder();
+        let target = Ipv4Addr::new(10, 0, 1, 1);
+
+        let own_request = ArpMessage::new_arp_request(OWN_MAC, OWN_IP, target);
+        inject(&transport, &own_request);
+        let responses = async_io::block_on(client.respond_next(&responder)).unwrap();
+        assert!(responses.is_empty());
+        assert!(transport.take_sent_frames().is_empty());
+
+        inject(&transport, &request_for(target));
+        let responses = async_io::block_on(client.respond_next(&responder)).unwrap();
+        assert_eq!(responses.len(), 1);
+
+        let sent = sent_messages(&transport);
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].source_protocol_address, target);
+        assert_eq!(sent[0].target_hardware_address, PEER_MAC);
+    }
+
+    fn inarp_request(target: MacAddr) -> ArpMessage {
+        ArpMessage::new_inarp_request(PEER_MAC, PEER_IP, target)
+    }
+
+    #[test]
+    fn inverse_reports_interface_address() {
+        let mut responder = ArpResponder::new();
+        assert!(responder
+            .respond_inverse(&inarp_request(OWN_MAC), OWN_MAC, Some(OWN_IP))
+            .is_empty());
+
+        responder.set_inverse(true);
+        let responses = responder.respond_inverse(&inarp_request(OWN_MAC), OWN_MAC, Some(OWN_IP));
+
+        assert_eq!(responses.len(), 1);
+        assert_eq!(responses[0].operation, Operation::InArpResponse);
+        assert_eq!(responses[0].source_hardware_address, OWN_MAC);
+        assert_eq!(responses[0].source_protocol_address, OWN_IP);
+        assert_eq!(responses[0].target_hardware_address, PEER_MAC);
+    }
+
+    #[test]
+    fn inverse_reports_each_configured_address() {
+        let mut responder = ArpResponder::new();
+        responder.set_inverse(true);
+        responder.add_inverse_address(Ipv4Addr::new(10, 0, 0, 10));
+        responder.add_inverse_address(Ipv4Addr::new(10, 0, 0, 11));
+
+        let addresses = responder
+            .respond_inverse(&inarp_request(OWN_MAC), OWN_MAC, Some(OWN_IP))
+            .iter()
+            .map(|response| response.source_protocol_address)
+            .collect::<Vec<Ipv4Addr>>();
+
+        assert_eq!(
+            addresses,
+            vec![Ipv4Addr::new(10, 0, 0, 10), Ipv4Addr::new(10, 0, 0, 11)]
+        );
+    }
+
+    #[test]
+    fn inverse_ignores_proxy_mac() {
+        let mut responder = ArpResponder::new();
+        responder.set_inverse(true);
+        responder.set_mac(PROXY_MAC);
+
+        assert!(responder
+            .respond_inverse(&inarp_request(PROXY_MAC), OWN_MAC, Some(OWN_IP))
+            .is_empty());
+        assert_eq!(
+            responder
+                .respond_inverse(&inarp_request(OWN_MAC), OWN_MAC, Some(OWN_IP))
+                .len(),
+            1
+        );
+    }
+}
@@ -0,0 +1,188 @@
+use crate::{
+    arp::{ArpMessage, Operation},
+    client::{blocking, ArpClient},
+    error::Error,
+    interfaces::MacAddr,
+    transport::Transport,
+};
+use pnet::ipnetwork::{Ipv4Network, Ipv4NetworkIterator};
+use std::{
+    collections::HashMap,
+    net::Ipv4Addr,
+    time::{Duration, Instant},
+};
+
+/// Options for scanning a subnet with `ArpClient::scan`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ScanOptions {
+    /// Network address and prefix length of the range to scan.
+    /// If None, the subnet of the client's interface is scanned.
+    pub network: Option<(Ipv4Addr, u8)>,
+    /// Maximum number of ARP requests sent per second.
+    pub rate: u32,
+    /// Time to wait for replies after the last request has been sent.
+    pub timeout: Duration,
+}
+
+impl Default for ScanOptions {
+    fn default() -> Self {
+        ScanOptions {
+            network: None,
+            rate: 100,
+            timeout: Duration::from_secs(1),
+        }
+    }
+}
+
+/// A host that answered an ARP request sent during a scan.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ScanResult {
+    /// IPv4 address the request has been sent for.
+    pub ip: Ipv4Addr,
+    /// Sender hardware address of the reply.
+    pub mac: MacAddr,
+    /// Time between sending the request for `ip` and receiving the first reply from `mac`.
+    pub latency: Duration,
+    /// Number of replies received from `mac` for `ip`.
+    pub reply_count: usize,
+}
+
+/// State of a running scan, independent of how requests are sent and replies are received.
+struct Scan {
+    hosts: Ipv4NetworkIterator,
+    network: Ipv4Network,
+    own_ip: Ipv4Addr,
+    send_times: HashMap<Ipv4Addr, Instant>,
+    results: HashMap<(Ipv4Addr, MacAddr), ScanResult>,
+}
+
+impl Scan {
+    fn new(network: Ipv4Addr, prefix: u8, own_ip: Ipv4Addr) -> Result<Self, Error> {
+        let network = match Ipv4Network::new(network, prefix) {
+            Ok(network) => network,
+            Err(err) => {
+                return Err(Error::InvalidInput {
+                    reason: err.to_string(),
+                })
+            }
+        };
+
+        Ok(Scan {
+            hosts: network.iter(),
+            network,
+            own_ip,
+            send_times: HashMap::new(),
+            results: HashMap::new(),
+        })
+    }
+
+    /// Returns the next address to send a request to.
+    /// Skips the own address, and the network and broadcast addresses of networks that have them.
+    fn next_host(&mut self) -> Option<Ipv4Addr> {
+        let own_ip = self.own_ip;
+        let network = self.network;
+        let has_network_broadcast = network.prefix() < 31;
+
+        self.hosts.find(|ip| {
+            *ip != own_ip
+                && !(has_network_broadcast
+                    && (*ip == network.network() || *ip == network.broadcast()))
+        })
+    }
+
+    fn mark_sent(&mut self, ip: Ipv4Addr, send_time: Instant) {
+        self.send_times.insert(ip, send_time);
+    }
+
+    fn handle_message(&mut self, message: &ArpMessage, receive_time: Instant) {
+        if message.operation != Operation::ArpResponse {
+            return;
+        }
+
+        let ip = message.source_protocol_address;
+        let mac = message.source_hardware_address;
+        let send_time = match self.send_times.get(&ip) {
+            Some(send_time) => *send_time,
+            None => return,
+        };
+
+        self.results
+            .entry((ip, mac))
+            .or_insert(ScanResult {
+                ip,
+                mac,
+                latency: receive_time.saturating_duration_since(send_time),
+                reply_count: 0,
+            })
+            .reply_count += 1;
+    }
+
+    fn into_results(self) -> Vec<ScanResult> {
+        let mut results = self.results.into_values().collect::<Vec<ScanResult>>();
+        results.sort_by_key(|result| (result.ip, result.mac));
+
+        results
+    }
+}
+
+impl<T: Transport> ArpClient<T> {
+    /// Scans a subnet by sending an ARP request to every host in it, paced to `options.rate` requests per second.
+    /// Collects all replies until `options.timeout` has passed after the last request.
+    /// Returns one result per IPv4/MAC pair that answered, ordered by IPv4 address.
+    pub async fn scan(&mut self, options: &ScanOptions) -> Result<Vec<ScanResult>, Error> {
+        if options.rate == 0 {
+            return Err(Error::InvalidInput {
+                reason: "Scan rate must be greater than zero.".to_string(),
+            });
+        }
+
+        let own_mac = self.get_interface().get_mac()?;
+        let own_ip = self.get_interface().get_ip()?;
+        let (network, prefix) = match options.network {
+            Some(network) => network,
+            None => (own_ip, self.get_interface().get_prefix()?),
+        };
+
+        let mut scan = Scan::new(network, prefix, own_ip)?;
+        let send_interval = Duration::from_secs(1) / options.rate;
+        let mut next_host = scan.next_host();
+        let mut next_send_time = Instant::now();
+        let mut last_send_time = Instant::now();
+
+        loop {
+            let now = Instant::now();
+            match next_host {
+                Some(ip) if now >= next_send_time => {
+                    self.send(&ArpMessage::new_arp_request(own_mac, own_ip, ip))
+                        .await?;
+                    scan.mark_sent(ip, now);
+
+                    last_send_time = now;
+                    // pace from the actual send time, so that a stall is not caught up with a burst
+                    next_send_time = Instant::now() + send_interval;
+                    next_host = scan.next_host();
+                }
+                None if now - last_send_time >= options.timeout => break,
+                _ => {}
+            }
+
+            // wait for replies until the next request is due or the scan ends
+            let deadline = match next_host {
+                Some(_) => next_send_time,
+                None => last_send_time + options.timeout,
+            };
+            if let Some(received) = self.receive_until(Some(deadline)).await? {
+                scan.handle_message(&received.message, received.received_at);
+            }
+        }
+
+        Ok(scan.into_results())
+    }
+}
+
+impl<T: Transport> blocking::ArpClient<T> {
+    /// Scans the network configured in `options` for hosts answering ARP requests, see `ArpClient::scan`.
+    pub fn scan(&mut self, options: &ScanOptions) -> Result<Vec<ScanResult>, Error> {
+        async_io::block_on(self.as_async().scan(options))
+    }
+}
@@ -0,0 +1,47 @@
+use crate::{
+    arp::ArpMessage,
+    client::{blocking, ArpClient},
+    error::Error,
+    transport::Transport,
+};
+use futures_core::Stream;
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// Stream of all ARP and RARP messages received by an `ArpClient`, see `ArpClient::messages`.
+/// Frames not containing an ARP or RARP message are skipped.
+pub struct ArpMessageStream<'a, T: Transport> {
+    client: &'a mut ArpClient<T>,
+}
+
+impl<T: Transport> Stream for ArpMessageStream<'_, T> {
+    type Item = Result<ArpMessage, Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.client
+            .poll_receive(cx)
+            .map(|result| Some(result.map(|received| received.message)))
+    }
+}
+
+impl<T: Transport> ArpClient<T> {
+    /// Returns a stream of all ARP and RARP messages received by the client.
+    /// The stream never ends, but yields an error when receiving fails.
+    pub fn messages(&mut self) -> ArpMessageStream<'_, T> {
+        ArpMessageStream { client: self }
+    }
+}
+
+impl<T: Transport> blocking::ArpClient<T> {
+    /// Returns an iterator over all ARP and RARP messages received by the client.
+    /// The iterator never ends, but yields an error when receiving fails.
+    pub fn messages(&mut self) -> impl Iterator<Item = Result<ArpMessage, Error>> + '_ {
+        std::iter::from_fn(move || {
+            async_io::block_on(self.as_async().receive_until(None))
+                .map(|received| received.map(|received| received.message))
+                .transpose()
+        })
+    }
+}
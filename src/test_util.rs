@@ -0,0 +1,43 @@
+use crate::{
+    arp::ArpMessage,
+    client::ArpClient,
+    interfaces::{Interface, MacAddr},
+    transport::MemoryTransport,
+};
+use std::net::Ipv4Addr;
+
+/// MAC address of the interface of the client returned by `client`.
+pub(crate) const OWN_MAC: MacAddr = MacAddr(0x02, 0, 0, 0, 0, 1);
+/// MAC address of another host on the network.
+pub(crate) const PEER_MAC: MacAddr = MacAddr(0x02, 0, 0, 0, 0, 2);
+/// MAC address of a third host, e.g. one spoofing or proxying the addresses of others.
+pub(crate) const OTHER_MAC: MacAddr = MacAddr(0x02, 0, 0, 0, 0, 3);
+/// IPv4 address of the interface of the client returned by `client`.
+pub(crate) const OWN_IP: Ipv4Addr = Ipv4Addr::new(10, 0, 0, 1);
+/// IPv4 address of another host on the network.
+pub(crate) const PEER_IP: Ipv4Addr = Ipv4Addr::new(10, 0, 0, 2);
+
+/// Returns a client on a virtual interface with `OWN_MAC` and `OWN_IP` in 10.0.0.0/24, along with its transport.
+pub(crate) fn client() -> (ArpClient<MemoryTransport>, MemoryTransport) {
+    let interface = Interface::new_virtual("test0", OWN_MAC, OWN_IP, 24).unwrap();
+    let transport = MemoryTransport::new();
+
+    (
+        ArpClient::new_with_transport(&interface, transport.clone()),
+        transport,
+    )
+}
+
+/// Lets `transport` receive `message`, sent from its sender hardware address.
+pub(crate) fn inject(transport: &MemoryTransport, message: &ArpMessage) {
+    transport.inject_frame(message.to_ethernet_frame(message.source_hardware_address));
+}
+
+/// Returns the messages sent on `transport` since the last call.
+pub(crate) fn sent_messages(transport: &MemoryTransport) -> Vec<ArpMessage> {
+    transport
+        .take_sent_frames()
+        .iter()
+        .map(|frame| ArpMessage::from_ethernet_frame(frame).unwrap())
+        .collect()
+}
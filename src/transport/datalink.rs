@@ -0,0 +1,91 @@
+use super::Transport;
+use crate::interfaces::Interface;
+use pnet::datalink::{DataLinkReceiver, DataLinkSender};
+use std::{
+    future::Future,
+    io::{Error, ErrorKind},
+    pin::Pin,
+    task::{Context, Poll},
+    thread,
+    time::{Duration, Instant},
+};
+
+/// Transport sending and receiving frames on a network interface using `pnet` datalink channels.
+/// Works on every platform supported by `pnet`, but can not notify the client about incoming frames,
+/// so waiting for frames is done by polling.
+pub struct PnetTransport {
+    tx_channel: Box<dyn DataLinkSender>,
+    rx_channel: Box<dyn DataLinkReceiver>,
+    frame: Vec<u8>,
+    poll_timer: Option<async_io::Timer>,
+}
+
+impl PnetTransport {
+    /// Interval in which the channel is polled while waiting for a frame.
+    const POLL_INTERVAL: Duration = Duration::from_millis(1);
+
+    /// Opens a new (tx, rx) channel pair on `interface`.
+    pub fn new(interface: &Interface) -> Result<Self, Error> {
+        let (tx_channel, rx_channel) = interface.create_tx_rx_channels()?;
+
+        Ok(PnetTransport {
+            tx_channel,
+            rx_channel,
+            frame: Vec::new(),
+            poll_timer: None,
+        })
+    }
+
+    /// Copies the next frame into `self.frame`, returns whether there was one.
+    fn try_receive(&mut self) -> Result<bool, Error> {
+        match self.rx_channel.next() {
+            Ok(frame) => {
+                self.frame.clear();
+                self.frame.extend_from_slice(frame);
+                Ok(true)
+            }
+            Err(err) if err.kind() == ErrorKind::TimedOut => Ok(false),
+            Err(err) => Err(err),
+        }
+    }
+}
+
+impl Transport for PnetTransport {
+    fn send_frame(&mut self, frame: &[u8]) -> Result<(), Error> {
+        match self.tx_channel.send_to(frame, None) {
+            Some(result) => result,
+            None => Err(Error::other("Could not send frame.")),
+        }
+    }
+
+    fn receive_frame(&mut self, timeout: Option<Duration>) -> Result<Option<&[u8]>, Error> {
+        let start_time = Instant::now();
+
+        while !self.try_receive()? {
+            if let Some(timeout) = timeout {
+                if Instant::now() - start_time >= timeout {
+                    return Ok(None);
+                }
+            }
+
+            thread::sleep(PnetTransport::POLL_INTERVAL);
+        }
+
+        Ok(Some(&self.frame))
+    }
+
+    fn poll_readable(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Error>> {
+        // there is no way to be notified about incoming frames, so report readiness once per poll interval
+        let timer = self
+            .poll_timer
+            .get_or_insert_with(|| async_io::Timer::after(PnetTransport::POLL_INTERVAL));
+
+        match Pin::new(timer).poll(cx) {
+            Poll::Ready(_) => {
+                self.poll_timer = None;
+                Poll::Ready(Ok(()))
+            }
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
@@ -0,0 +1,102 @@
+use super::Transport;
+use std::{
+    collections::VecDeque,
+    io::Error,
+    sync::{Arc, Condvar, Mutex},
+    task::{Context, Poll, Waker},
+    time::{Duration, Instant},
+};
+
+/// Transport that keeps frames in memory instead of putting them on the wire.
+/// Useful for testing code built on `ArpClient` without raw sockets or a real network interface.
+///
+/// Clones share the same queues, so a clone can be kept to inject frames into
+/// and inspect frames sent by a client owning the transport.
+#[derive(Clone, Default)]
+pub struct MemoryTransport {
+    shared: Arc<Shared>,
+    current_frame: Vec<u8>,
+}
+
+#[derive(Default)]
+struct Shared {
+    queues: Mutex<MemoryQueues>,
+    frame_injected: Condvar,
+}
+
+#[derive(Default)]
+struct MemoryQueues {
+    incoming: VecDeque<Vec<u8>>,
+    sent: Vec<Vec<u8>>,
+    waker: Option<Waker>,
+}
+
+impl MemoryTransport {
+    /// Creates a new transport with empty queues.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Queues `frame` to be received by the transport.
+    pub fn inject_frame(&self, frame: Vec<u8>) {
+        let mut queues = self.shared.queues.lock().unwrap();
+        queues.incoming.push_back(frame);
+
+        if let Some(waker) = queues.waker.take() {
+            waker.wake();
+        }
+        self.shared.frame_injected.notify_all();
+    }
+
+    /// Returns all frames sent through the transport so far and clears them.
+    pub fn take_sent_frames(&self) -> Vec<Vec<u8>> {
+        std::mem::take(&mut self.shared.queues.lock().unwrap().sent)
+    }
+}
+
+impl Transport for MemoryTransport {
+    fn send_frame(&mut self, frame: &[u8]) -> Result<(), Error> {
+        self.shared.queues.lock().unwrap().sent.push(frame.to_vec());
+        Ok(())
+    }
+
+    fn receive_frame(&mut self, timeout: Option<Duration>) -> Result<Option<&[u8]>, Error> {
+        let start_time = Instant::now();
+        let mut queues = self.shared.queues.lock().unwrap();
+
+        loop {
+            if let Some(frame) = queues.incoming.pop_front() {
+                drop(queues);
+                self.current_frame = frame;
+                return Ok(Some(&self.current_frame));
+            }
+
+            queues = match timeout {
+                Some(timeout) => {
+                    let elapsed = Instant::now() - start_time;
+                    if elapsed >= timeout {
+                        return Ok(None);
+                    }
+
+                    self.shared
+                        .frame_injected
+                        .wait_timeout(queues, timeout - elapsed)
+                        .unwrap()
+                        .0
+                }
+                None => self.shared.frame_injected.wait(queues).unwrap(),
+            };
+        }
+    }
+
+    fn poll_readable(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Error>> {
+        let mut queues = self.shared.queues.lock().unwrap();
+
+        if queues.incoming.is_empty() {
+            queues.waker = Some(cx.waker().clone());
+            Poll::Pending
+        } else {
+            Poll::Ready(Ok(()))
+        }
+    }
+}
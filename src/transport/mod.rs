@@ -0,0 +1,48 @@
+mod datalink;
+mod memory;
+#[cfg(target_os = "linux")]
+mod packet_socket;
+
+pub use datalink::PnetTransport;
+pub use memory::MemoryTransport;
+#[cfg(target_os = "linux")]
+pub use packet_socket::PacketSocketTransport;
+
+use std::{
+    io::Error,
+    task::{Context, Poll},
+    time::Duration,
+};
+
+/// Transport used by `ArpClient` if none is given explicitly.
+/// On Linux, this is `PacketSocketTransport`, elsewhere `PnetTransport`.
+#[cfg(target_os = "linux")]
+pub type DefaultTransport = PacketSocketTransport;
+/// Transport used by `ArpClient` if none is given explicitly.
+/// On Linux, this is `PacketSocketTransport`, elsewhere `PnetTransport`.
+#[cfg(not(target_os = "linux"))]
+pub type DefaultTransport = PnetTransport;
+
+/// Link-layer transport used by `ArpClient` to send and receive raw Ethernet frames.
+pub trait Transport {
+    /// Sends the raw Ethernet `frame`.
+    /// # Errors
+    /// Returns an error when sending fails.
+    fn send_frame(&mut self, frame: &[u8]) -> Result<(), Error>;
+
+    /// Returns the next received raw Ethernet frame, blocking for at most `timeout` (or forever if None).
+    /// Returns None if no frame has been received in time. With a `timeout` of zero, never blocks.
+    /// # Errors
+    /// Returns an error when receiving fails.
+    fn receive_frame(&mut self, timeout: Option<Duration>) -> Result<Option<&[u8]>, Error>;
+
+    /// Polls whether a frame can be received without blocking. If not, arranges for the task of `cx`
+    /// to be woken up when this changes. Used by the async client to wait for frames.
+    ///
+    /// The default implementation does not know when frames arrive, so it wakes up the task immediately,
+    /// which amounts to busy polling. Transports should override it if possible.
+    fn poll_readable(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Error>> {
+        cx.waker().wake_by_ref();
+        Poll::Pending
+    }
+}
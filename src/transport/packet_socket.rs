@@ -0,0 +1,299 @@
+use super::Transport;
+use crate::interfaces::Interface;
+use crate::vlan::VLAN_TAG_SIZE;
+use std::{
+    io::{Error, ErrorKind},
+    mem,
+    os::fd::{AsFd, AsRawFd, BorrowedFd, FromRawFd, OwnedFd},
+    ptr,
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+
+/// Transport sending and receiving frames on a network interface using a Linux `AF_PACKET` socket.
+///
+/// The socket is non-blocking and registered with the `async-io` reactor (blocking reads use `poll`),
+/// so waiting for frames never busy polls.
+/// A socket filter makes sure that only (R)ARP and VLAN-tagged frames are received.
+/// VLAN tags stripped from received frames by the kernel (VLAN offloading) are reinserted.
+pub struct PacketSocketTransport {
+    socket: async_io::Async<PacketSocket>,
+    buffer: Vec<u8>,
+    frame_len: usize,
+}
+
+/// Raw `AF_PACKET` socket bound to a single interface.
+struct PacketSocket {
+    fd: OwnedFd,
+}
+
+impl PacketSocketTransport {
+    /// Size of the receive buffer, large enough for any frame received on common links.
+    const BUFFER_SIZE: usize = 65536;
+
+    /// Opens a new packet socket on `interface`.
+    pub fn new(interface: &Interface) -> Result<Self, Error> {
+        let socket = PacketSocket::new(interface.get_raw_interface().index)?;
+
+        Ok(PacketSocketTransport {
+            socket: async_io::Async::new(socket)?,
+            buffer: vec![0; PacketSocketTransport::BUFFER_SIZE],
+            frame_len: 0,
+        })
+    }
+}
+
+impl Transport for PacketSocketTransport {
+    fn send_frame(&mut self, frame: &[u8]) -> Result<(), Error> {
+        loop {
+            match self.socket.get_ref().send(frame) {
+                Err(err) if err.kind() == ErrorKind::WouldBlock => {
+                    // the send queue of the socket is full, which is rare and does not last long
+                    self.socket.get_ref().wait(libc::POLLOUT, None)?;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    fn receive_frame(&mut self, timeout: Option<Duration>) -> Result<Option<&[u8]>, Error> {
+        loop {
+            match self.socket.get_ref().receive(&mut self.buffer) {
+                Ok(frame_len) => {
+                    self.frame_len = frame_len;
+                    break;
+                }
+                Err(err) if err.kind() == ErrorKind::WouldBlock => {
+                    if timeout == Some(Duration::ZERO)
+                        || !self.socket.get_ref().wait(libc::POLLIN, timeout)?
+                    {
+                        return Ok(None);
+                    }
+                }
+                Err(err) => return Err(err),
+            }
+        }
+
+        Ok(Some(&self.buffer[..self.frame_len]))
+    }
+
+    fn poll_readable(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Error>> {
+        self.socket.poll_readable(cx)
+    }
+}
+
+impl PacketSocket {
+    /// Classic BPF program accepting frames with the ethertypes ARP, RARP and VLAN (802.1Q, 802.1ad, QinQ).
+    const FILTER: [libc::sock_filter; 8] = [
+        // load the ethertype
+        bpf_statement(0x28, 12),
+        bpf_jump(0x15, 0x0806, 5, 0),
+        bpf_jump(0x15, 0x8035, 4, 0),
+        bpf_jump(0x15, 0x8100, 3, 0),
+        bpf_jump(0x15, 0x88a8, 2, 0),
+        bpf_jump(0x15, 0x9100, 1, 0),
+        // drop
+        bpf_statement(0x06, 0),
+        // accept the whole frame
+        bpf_statement(0x06, 0xffff_ffff),
+    ];
+
+    fn new(interface_index: u32) -> Result<Self, Error> {
+        // with protocol 0, the socket receives nothing until it is bound to the interface and all protocols,
+        // so no frames of other interfaces are queued before the filter and the binding apply
+        let fd = unsafe {
+            libc::socket(
+                libc::AF_PACKET,
+                libc::SOCK_RAW | libc::SOCK_NONBLOCK | libc::SOCK_CLOEXEC,
+                0,
+            )
+        };
+        if fd == -1 {
+            return Err(Error::last_os_error());
+        }
+        let socket = PacketSocket {
+            fd: unsafe { OwnedFd::from_raw_fd(fd) },
+        };
+
+        let filter = libc::sock_fprog {
+            len: PacketSocket::FILTER.len() as libc::c_ushort,
+            filter: PacketSocket::FILTER.as_ptr() as *mut libc::sock_filter,
+        };
+        let result = unsafe {
+            libc::setsockopt(
+                fd,
+                libc::SOL_SOCKET,
+                libc::SO_ATTACH_FILTER,
+                &filter as *const libc::sock_fprog as *const libc::c_void,
+                mem::size_of::<libc::sock_fprog>() as libc::socklen_t,
+            )
+        };
+        if result == -1 {
+            return Err(Error::last_os_error());
+        }
+
+        // report VLAN tags stripped by the kernel with every received frame
+        let enable: libc::c_int = 1;
+        let result = unsafe {
+            libc::setsockopt(
+                fd,
+                libc::SOL_PACKET,
+                libc::PACKET_AUXDATA,
+                &enable as *const libc::c_int as *const libc::c_void,
+                mem::size_of::<libc::c_int>() as libc::socklen_t,
+            )
+        };
+        if result == -1 {
+            return Err(Error::last_os_error());
+        }
+
+        let mut address: libc::sockaddr_ll = unsafe { mem::zeroed() };
+        address.sll_family = libc::AF_PACKET as libc::c_ushort;
+        address.sll_protocol = (libc::ETH_P_ALL as u16).to_be();
+        address.sll_ifindex = interface_index as libc::c_int;
+        let result = unsafe {
+            libc::bind(
+                fd,
+                &address as *const libc::sockaddr_ll as *const libc::sockaddr,
+                mem::size_of::<libc::sockaddr_ll>() as libc::socklen_t,
+            )
+        };
+        if result == -1 {
+            return Err(Error::last_os_error());
+        }
+
+        Ok(socket)
+    }
+
+    fn send(&self, frame: &[u8]) -> Result<(), Error> {
+        let result = unsafe {
+            libc::send(
+                self.fd.as_raw_fd(),
+                frame.as_ptr() as *const libc::c_void,
+                frame.len(),
+                0,
+            )
+        };
+
+        if result == -1 {
+            Err(Error::last_os_error())
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Receives a frame into `buffer`, returns its length.
+    /// A VLAN tag stripped by the kernel is reinserted into the frame.
+    fn receive(&self, buffer: &mut [u8]) -> Result<usize, Error> {
+        let mut iov = libc::iovec {
+            iov_base: buffer.as_mut_ptr() as *mut libc::c_void,
+            // leave room to reinsert a VLAN tag
+            iov_len: buffer.len() - VLAN_TAG_SIZE,
+        };
+        // u64 for the alignment required by control messages
+        let mut control = [0u64; 8];
+
+        let mut header: libc::msghdr = unsafe { mem::zeroed() };
+        header.msg_iov = &mut iov;
+        header.msg_iovlen = 1;
+        header.msg_control = control.as_mut_ptr() as *mut libc::c_void;
+        header.msg_controllen = mem::size_of_val(&control) as _;
+
+        let result = unsafe { libc::recvmsg(self.fd.as_raw_fd(), &mut header, 0) };
+        if result == -1 {
+            return Err(Error::last_os_error());
+        }
+        let mut frame_len = result as usize;
+
+        if let Some(auxdata) = unsafe { PacketSocket::auxdata(&header) } {
+            if auxdata.tp_status & libc::TP_STATUS_VLAN_VALID != 0 && frame_len >= 12 {
+                let tpid = if auxdata.tp_status & libc::TP_STATUS_VLAN_TPID_VALID != 0 {
+                    auxdata.tp_vlan_tpid
+                } else {
+                    0x8100
+                };
+
+                // the tag belongs between the MAC addresses and the ethertype
+                buffer.copy_within(12..frame_len, 12 + VLAN_TAG_SIZE);
+                buffer[12..14].copy_from_slice(&tpid.to_be_bytes());
+                buffer[14..16].copy_from_slice(&auxdata.tp_vlan_tci.to_be_bytes());
+                frame_len += VLAN_TAG_SIZE;
+            }
+        }
+
+        Ok(frame_len)
+    }
+
+    /// Returns the `PACKET_AUXDATA` control message received with `header`, if any.
+    /// # Safety
+    /// `header` must describe a message received with `recvmsg`.
+    unsafe fn auxdata(header: &libc::msghdr) -> Option<libc::tpacket_auxdata> {
+        let mut control_message = libc::CMSG_FIRSTHDR(header);
+
+        while !control_message.is_null() {
+            if (*control_message).cmsg_level == libc::SOL_PACKET
+                && (*control_message).cmsg_type == libc::PACKET_AUXDATA
+            {
+                let data = libc::CMSG_DATA(control_message) as *const libc::tpacket_auxdata;
+                return Some(ptr::read_unaligned(data));
+            }
+
+            control_message = libc::CMSG_NXTHDR(header, control_message);
+        }
+
+        None
+    }
+
+    /// Waits until `events` occur on the socket for at most `timeout` (or forever if None).
+    /// Returns whether the events occurred in time.
+    fn wait(&self, events: libc::c_short, timeout: Option<Duration>) -> Result<bool, Error> {
+        let deadline = timeout.and_then(|timeout| Instant::now().checked_add(timeout));
+
+        let mut poll_fd = libc::pollfd {
+            fd: self.fd.as_raw_fd(),
+            events,
+            revents: 0,
+        };
+
+        loop {
+            // recomputed on every attempt, so that interruptions by signals do not extend the timeout
+            let timeout_ms = match (timeout, deadline) {
+                // round up, so that waiting does not end before the timeout
+                (Some(_), Some(deadline)) => deadline
+                    .saturating_duration_since(Instant::now())
+                    .as_nanos()
+                    .div_ceil(1_000_000)
+                    .min(libc::c_int::MAX as u128)
+                    as libc::c_int,
+                (Some(_), None) => libc::c_int::MAX,
+                (None, _) => -1,
+            };
+
+            match unsafe { libc::poll(&mut poll_fd, 1, timeout_ms) } {
+                -1 if Error::last_os_error().kind() == ErrorKind::Interrupted => continue,
+                -1 => return Err(Error::last_os_error()),
+                0 => return Ok(false),
+                _ => return Ok(true),
+            }
+        }
+    }
+}
+
+impl AsFd for PacketSocket {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.fd.as_fd()
+    }
+}
+
+const fn bpf_statement(code: u16, k: u32) -> libc::sock_filter {
+    libc::sock_filter {
+        code,
+        jt: 0,
+        jf: 0,
+        k,
+    }
+}
+
+const fn bpf_jump(code: u16, k: u32, jt: u8, jf: u8) -> libc::sock_filter {
+    libc::sock_filter { code, jt, jf, k }
+}
@@ -0,0 +1,67 @@
+use pnet::packet::{
+    ethernet::{EtherType, EtherTypes},
+    vlan::{ClassOfService, MutableVlanPacket, VlanPacket},
+};
+
+/// Size of a single VLAN tag in an Ethernet frame in bytes.
+pub const VLAN_TAG_SIZE: usize = 4;
+
+/// VLAN tag (IEEE 802.1Q) of an Ethernet frame.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct VlanTag {
+    /// Tag protocol identifier, e.g. `EtherTypes::Vlan` (802.1Q) or `EtherTypes::PBridge` (802.1ad).
+    pub tpid: EtherType,
+    /// Priority code point (0-7).
+    pub priority: u8,
+    /// Drop eligible indicator.
+    pub drop_eligible: bool,
+    /// VLAN identifier (0-4095).
+    pub id: u16,
+}
+
+impl VlanTag {
+    /// Constructs a new 802.1Q (customer) VLAN tag with the VLAN `id` and `priority`.
+    pub fn new(id: u16, priority: u8) -> Self {
+        VlanTag {
+            tpid: EtherTypes::Vlan,
+            priority,
+            drop_eligible: false,
+            id,
+        }
+    }
+
+    /// Constructs a new 802.1ad (service) VLAN tag with the VLAN `id` and `priority`,
+    /// used as outer tag of QinQ frames.
+    pub fn new_service(id: u16, priority: u8) -> Self {
+        VlanTag {
+            tpid: EtherTypes::PBridge,
+            ..VlanTag::new(id, priority)
+        }
+    }
+
+    /// Returns whether `ethertype` is the tag protocol identifier of a VLAN tag.
+    pub fn is_tpid(ethertype: EtherType) -> bool {
+        ethertype == EtherTypes::Vlan
+            || ethertype == EtherTypes::PBridge
+            || ethertype == EtherTypes::QinQ
+    }
+
+    /// Decodes the tag with the protocol identifier `tpid` from `vlan_packet`, the part of a frame following `tpid`.
+    pub(crate) fn from_packet(tpid: EtherType, vlan_packet: &VlanPacket) -> Self {
+        VlanTag {
+            tpid,
+            priority: vlan_packet.get_priority_code_point().0,
+            drop_eligible: vlan_packet.get_drop_eligible_indicator() == 1,
+            id: vlan_packet.get_vlan_identifier(),
+        }
+    }
+
+    /// Encodes the tag into `vlan_packet`, followed by `ethertype`, the ethertype of the frame or the next tag.
+    /// Out of range values for the priority and VLAN ID are truncated.
+    pub(crate) fn write_to(&self, vlan_packet: &mut MutableVlanPacket, ethertype: EtherType) {
+        vlan_packet.set_priority_code_point(ClassOfService::new(self.priority & 0x7));
+        vlan_packet.set_drop_eligible_indicator(self.drop_eligible as u8);
+        vlan_packet.set_vlan_identifier(self.id & 0xfff);
+        vlan_packet.set_ethertype(ethertype);
+    }
+}